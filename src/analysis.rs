@@ -0,0 +1,331 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::{self, Display, Formatter},
+};
+
+use crate::ast::{Declaration, InlineType, Statement, Template, TypedefAlias};
+
+/// A problem in a parsed template that the grammar alone does not catch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AnalysisError {
+    /// A declaration passes a different number of arguments than the
+    /// parameterized struct it instantiates takes.
+    ArgumentCount {
+        variable: String,
+        ty: String,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl Display for AnalysisError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            AnalysisError::ArgumentCount {
+                variable,
+                ty,
+                expected,
+                found,
+            } => write!(
+                f,
+                "`{}` passes {} argument(s) to `{}`, which takes {}",
+                variable, found, ty, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for AnalysisError {}
+
+/// The statements nested inside `statement`, such as a struct body.
+fn children(statement: &Statement) -> Vec<&Statement> {
+    let body: &[Statement] = match statement {
+        Statement::Typedef(typedef) => &typedef.body,
+        Statement::TypedefUnion(typedef) => &typedef.body,
+        Statement::Union(union) => &union.body,
+        Statement::Inline(inline) => match &inline.ty {
+            InlineType::Struct(typedef) => &typedef.body,
+            InlineType::Union(typedef) => &typedef.body,
+            InlineType::Enum(_) => &[],
+        },
+        Statement::While(statement) => &statement.body,
+        Statement::DoWhile(statement) => &statement.body,
+        Statement::For(statement) => &statement.body,
+        Statement::Function(function) => function.body.as_deref().unwrap_or_default(),
+        Statement::If(statement) => {
+            let branches = statement.else_ifs.iter().map(|branch| &branch.body);
+            return [&statement.body]
+                .into_iter()
+                .chain(branches)
+                .chain(&statement.else_body)
+                .flatten()
+                .collect();
+        }
+        Statement::Switch(statement) => {
+            return statement.cases.iter().flat_map(|case| &case.body).collect();
+        }
+        _ => &[],
+    };
+    body.iter().collect()
+}
+
+/// Records how many parameters each struct typedef takes, under both its
+/// tag name and its alias.
+fn collect_parameter_counts<'a>(
+    items: impl IntoIterator<Item = &'a Statement>,
+    counts: &mut HashMap<&'a str, usize>,
+) {
+    for item in items {
+        if let Statement::Typedef(typedef) = item {
+            counts.insert(&typedef.alias, typedef.params.len());
+            if let Some(name) = &typedef.name {
+                counts.insert(name, typedef.params.len());
+            }
+        }
+        collect_parameter_counts(children(item), counts);
+    }
+}
+
+fn check_arguments(
+    declaration: &Declaration,
+    counts: &HashMap<&str, usize>,
+) -> Option<AnalysisError> {
+    let expected = *counts.get(declaration.ty.name.as_str())?;
+    if declaration.args.len() == expected {
+        return None;
+    }
+    Some(AnalysisError::ArgumentCount {
+        variable: declaration.name.clone().unwrap_or_default(),
+        ty: declaration.ty.name.clone(),
+        expected,
+        found: declaration.args.len(),
+    })
+}
+
+fn check_statements<'a>(
+    items: impl IntoIterator<Item = &'a Statement>,
+    counts: &HashMap<&str, usize>,
+    errors: &mut Vec<AnalysisError>,
+) {
+    for item in items {
+        if let Statement::Declaration(declaration) = item {
+            errors.extend(check_arguments(declaration, counts));
+        }
+        check_statements(children(item), counts, errors);
+    }
+}
+
+/// Checks a parsed template for mistakes the parser accepts, returning every
+/// one found in source order.
+///
+/// Each declaration of a struct typedef must pass exactly as many arguments
+/// as the typedef declares parameters. Types that are not struct typedefs in
+/// this template, such as built-in types, are not checked.
+///
+/// # Example
+///
+/// ```
+/// use bt_parser::{analysis::analyze, parse_template};
+///
+/// let template = parse_template(
+///     "typedef struct (int size) { ubyte data[size]; } Blob;\nBlob a(4);\nBlob b;\n",
+/// )
+/// .unwrap();
+/// let errors = analyze(&template);
+/// assert_eq!(errors.len(), 1);
+/// assert_eq!(
+///     errors[0].to_string(),
+///     "`b` passes 0 argument(s) to `Blob`, which takes 1"
+/// );
+/// ```
+pub fn analyze(template: &Template) -> Vec<AnalysisError> {
+    let mut counts = HashMap::new();
+    collect_parameter_counts(&template.items, &mut counts);
+    let mut errors = Vec::new();
+    check_statements(&template.items, &counts, &mut errors);
+    errors
+}
+
+fn collect_aliases<'a>(
+    items: impl IntoIterator<Item = &'a Statement>,
+    aliases: &mut HashMap<&'a str, &'a TypedefAlias>,
+) {
+    for item in items {
+        if let Statement::TypedefAlias(typedef) = item {
+            aliases.insert(&typedef.alias, typedef);
+        }
+        collect_aliases(children(item), aliases);
+    }
+}
+
+/// The alias typedefs of a template, such as `typedef char MAGIC[4];`, by
+/// alias name.
+#[derive(Debug, Default)]
+pub struct TypeAliases<'a> {
+    aliases: HashMap<&'a str, &'a TypedefAlias>,
+}
+
+impl<'a> TypeAliases<'a> {
+    /// Collects every alias typedef in `template`, including those nested in
+    /// struct bodies.
+    pub fn new(template: &'a Template) -> Self {
+        let mut aliases = HashMap::new();
+        collect_aliases(&template.items, &mut aliases);
+        TypeAliases { aliases }
+    }
+
+    /// `declaration` as if written without aliases. Its type becomes the
+    /// aliased type, the alias bounds follow its own, and it inherits each
+    /// alias attribute it does not set itself. Aliases of aliases are
+    /// followed all the way down.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bt_parser::{analysis::TypeAliases, ast::Statement, parse_template};
+    ///
+    /// let template = parse_template("typedef char MAGIC[4] <format=hex>;\nMAGIC magic[2];\n").unwrap();
+    /// let Statement::Declaration(declaration) = &template.items[1] else {
+    ///     panic!("Expected Statement::Declaration");
+    /// };
+    /// let resolved = TypeAliases::new(&template).resolve(declaration);
+    /// assert_eq!(resolved.ty, "char".into());
+    /// assert_eq!(resolved.dimensions.len(), 2);
+    /// assert_eq!(resolved.attributes[0].value, "hex");
+    /// ```
+    pub fn resolve(&self, declaration: &Declaration) -> Declaration {
+        let mut resolved = declaration.clone();
+        let mut seen = HashSet::new();
+        while let Some(typedef) = self.aliases.get(resolved.ty.name.as_str()) {
+            // A cycle such as `typedef A B; typedef B A;` names no real type.
+            if !seen.insert(typedef.alias.as_str()) {
+                break;
+            }
+            resolved.ty = typedef.ty.clone();
+            resolved
+                .dimensions
+                .extend(typedef.dimensions.iter().cloned());
+            for attribute in &typedef.attributes {
+                if !resolved.attributes.iter().any(|a| a.name == attribute.name) {
+                    resolved.attributes.push(attribute.clone());
+                }
+            }
+        }
+        resolved
+    }
+}
+
+#[cfg(test)]
+mod analysis_tests {
+    use super::*;
+    use crate::{
+        ast::{Expr, Literal},
+        parse_template,
+    };
+    use pretty_assertions::assert_eq;
+
+    fn analyze_source(input: &str) -> Vec<AnalysisError> {
+        analyze(&parse_template(input).unwrap())
+    }
+
+    #[test]
+    fn test_analyze_argument_count() {
+        let input = r#"typedef struct (int size, int size2) {
+    ubyte data[size];
+} EquipInventoryData;
+
+typedef struct Player {
+    EquipInventoryData inv(0x780, 0x80);
+    EquipInventoryData spare(0x10);
+} PlayerGameData;
+EquipInventoryData extra(1, 2, 3);
+switch (1) {
+    case 1:
+        Player player(1);
+}
+uint32 unk(1);
+"#;
+        assert_eq!(
+            analyze_source(input),
+            vec![
+                AnalysisError::ArgumentCount {
+                    variable: "spare".into(),
+                    ty: "EquipInventoryData".into(),
+                    expected: 2,
+                    found: 1,
+                },
+                AnalysisError::ArgumentCount {
+                    variable: "extra".into(),
+                    ty: "EquipInventoryData".into(),
+                    expected: 2,
+                    found: 3,
+                },
+                AnalysisError::ArgumentCount {
+                    variable: "player".into(),
+                    ty: "Player".into(),
+                    expected: 0,
+                    found: 1,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_analyze_valid_template() {
+        let input = "typedef struct (int n) { ubyte b[n]; } Blob;\nBlob blob(4);\nint a;\n";
+        assert!(analyze_source(input).is_empty());
+    }
+
+    #[test]
+    fn test_resolve_aliases() {
+        let input = r#"typedef uint32 ItemHandle <format=hex, comment="handle">;
+typedef ItemHandle Handles[2];
+typedef struct {
+    Handles handles[3] <format=decimal>;
+    uint32 plain;
+} Inventory;
+"#;
+        let template = parse_template(input).unwrap();
+        let aliases = TypeAliases::new(&template);
+        let Statement::Typedef(inventory) = &template.items[2] else {
+            panic!("Expected Statement::Typedef");
+        };
+        let Statement::Declaration(handles) = &inventory.body[0] else {
+            panic!("Expected Statement::Declaration");
+        };
+        let resolved = aliases.resolve(handles);
+        assert_eq!(resolved.ty, "uint32".into());
+        assert_eq!(
+            resolved.dimensions,
+            vec![
+                Some(Expr::Literal(Literal::Int(3))),
+                Some(Expr::Literal(Literal::Int(2))),
+            ]
+        );
+        let attributes: Vec<_> = resolved
+            .attributes
+            .iter()
+            .map(|a| (a.name.as_str(), a.value.as_str()))
+            .collect();
+        assert_eq!(
+            attributes,
+            vec![("format", "decimal"), ("comment", "\"handle\"")]
+        );
+        let Statement::Declaration(plain) = &inventory.body[1] else {
+            panic!("Expected Statement::Declaration");
+        };
+        assert_eq!(&aliases.resolve(plain), plain);
+    }
+
+    #[test]
+    fn test_resolve_alias_cycle() {
+        let template = parse_template("typedef A B;\ntypedef B A;\nA a;\n").unwrap();
+        let Statement::Declaration(declaration) = &template.items[2] else {
+            panic!("Expected Statement::Declaration");
+        };
+        assert_eq!(
+            TypeAliases::new(&template).resolve(declaration).ty,
+            "A".into()
+        );
+    }
+}
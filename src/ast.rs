@@ -1,9 +1,355 @@
-use nom::{error::Error, IResult, InputLength};
+use nom::InputLength;
 
+use crate::{error::ParseError, metadata::TemplateMetadata};
+
+/// A fully parsed `.bt` template: its top-level items in source order.
 #[derive(Debug, PartialEq)]
+pub struct Template {
+    pub items: Vec<Statement>,
+    /// The header banner, if the template starts with one.
+    pub metadata: Option<TemplateMetadata>,
+    /// Problems that did not stop parsing, such as a header banner that
+    /// could not be decoded and so left [`Template::metadata`] empty.
+    pub warnings: Vec<ParseError>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Statement {
+    Comment(String),
+    Typedef(TypedefStruct),
+    TypedefEnum(TypedefEnum),
+    TypedefUnion(TypedefUnion),
+    TypedefAlias(TypedefAlias),
+    Union(UnionDeclaration),
+    Inline(InlineDeclaration),
+    Declaration(Declaration),
+    Variable(Variable),
+    If(IfStatement),
+    Function(Function),
+    While(WhileStatement),
+    DoWhile(DoWhileStatement),
+    For(ForStatement),
+    Switch(SwitchStatement),
+    Break,
+    Continue,
+    /// `return [value];`
+    Return(Option<Expr>),
+    /// An expression evaluated for its effect, e.g. `Printf("%d", a);`.
+    Expression(Expr),
+}
+
+/// A reference to a type by name, e.g. `uint32` or `PlayerGameData`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeRef {
+    pub name: String,
+}
+
+impl From<&str> for TypeRef {
+    fn from(name: &str) -> Self {
+        TypeRef { name: name.into() }
+    }
+}
+
+/// A special attribute written between `<` and `>`, e.g. `<format=hex>`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Attribute {
+    pub name: String,
+    /// The value as written, e.g. `(this < 0 ? cRed : cNone)`.
+    pub value: String,
+    /// The value parsed as an expression.
+    pub expr: Expr,
+}
+
+/// A parameter of a parameterized struct or a function, e.g. `int size` in
+/// `typedef struct (int size) { ... } Foo;` or `ItemEntry &e`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Parameter {
+    pub ty: TypeRef,
+    pub name: String,
+    /// Passed by reference, as in `int &count`.
+    pub by_ref: bool,
+    pub is_const: bool,
+    /// An unsized array such as `uchar data[]`.
+    pub is_array: bool,
+}
+
+/// `typedef struct [name] [(params)] { body } alias [<attributes>];`
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypedefStruct {
+    pub name: Option<String>,
+    pub params: Vec<Parameter>,
+    /// The members and control flow between the braces, in source order.
+    pub body: Vec<Statement>,
+    pub alias: String,
+    pub attributes: Vec<Attribute>,
+    /// The comment written directly above or trailing the typedef.
+    pub doc: Option<String>,
+}
+
+/// `typedef type alias [bounds] [<attributes>];`, a new name for an existing
+/// type such as `typedef uint32 ItemHandle <format=hex>;` or
+/// `typedef char MAGIC[4];`.
+///
+/// A declaration using the alias inherits its bounds and attributes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypedefAlias {
+    pub ty: TypeRef,
+    pub alias: String,
+    /// The bounds of an array alias, outermost first.
+    pub dimensions: Vec<Option<Expr>>,
+    pub attributes: Vec<Attribute>,
+    /// The comment written directly above or trailing the typedef.
+    pub doc: Option<String>,
+}
+
+/// A user function such as `string ReadName(Entry &e) { ... }`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Function {
+    pub return_type: TypeRef,
+    pub name: String,
+    pub params: Vec<Parameter>,
+    /// `None` for a forward declaration such as `void Skip(int n);`.
+    pub body: Option<Vec<Statement>>,
+    /// The comment written directly above or trailing the function.
+    pub doc: Option<String>,
+}
+
+/// `typedef union [name] { body } alias [<attributes>];`. Unlike a struct,
+/// every member starts at the same offset.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypedefUnion {
+    pub name: Option<String>,
+    pub body: Vec<Statement>,
+    pub alias: String,
+    pub attributes: Vec<Attribute>,
+    /// The comment written directly above or trailing the typedef.
+    pub doc: Option<String>,
+}
+
+/// An inline `union [name] { body } [variable] [<attributes>];`. A union
+/// declaring a variable is parsed as an [`InlineDeclaration`] instead, so
+/// within a template this is an anonymous union whose members are accessed
+/// directly.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnionDeclaration {
+    pub name: Option<String>,
+    pub body: Vec<Statement>,
+    pub variable: Option<String>,
+    pub attributes: Vec<Attribute>,
+    /// The comment written directly above or trailing the declaration.
+    pub doc: Option<String>,
+}
+
+/// A declaration whose type is defined in place, such as
+/// `struct { uint16 x, y; } pos;` or `enum <ubyte> { OFF, ON } state;`.
+///
+/// The type is recorded as the typedef it stands for. An anonymous type gets
+/// a generated alias such as `struct@pos`, which cannot clash with a name in
+/// the template, and every declaration uses that alias as its type.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InlineDeclaration {
+    pub ty: InlineType,
+    pub declarations: Vec<Declaration>,
+    /// The comment written directly above or trailing the declaration.
+    pub doc: Option<String>,
+}
+
+/// The type defined by an [`InlineDeclaration`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum InlineType {
+    Struct(TypedefStruct),
+    Union(TypedefUnion),
+    Enum(TypedefEnum),
+}
+
+impl InlineType {
+    /// The name the declarations refer to the type by.
+    pub fn alias(&self) -> &str {
+        match self {
+            InlineType::Struct(typedef) => &typedef.alias,
+            InlineType::Union(typedef) => &typedef.alias,
+            InlineType::Enum(typedef) => &typedef.alias,
+        }
+    }
+}
+
+/// `typedef enum [<type>] [name] { constants } alias [<attributes>];`
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypedefEnum {
+    pub name: Option<String>,
+    /// The backing type written as `<uchar>`; 010 Editor defaults to `int`.
+    pub ty: Option<TypeRef>,
+    pub constants: Vec<EnumConstant>,
+    pub alias: String,
+    pub attributes: Vec<Attribute>,
+    /// The comment written directly above or trailing the typedef.
+    pub doc: Option<String>,
+}
+
+/// A named enum value, e.g. `B = 5`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnumConstant {
+    pub name: String,
+    /// The expression after `=`, if one was written.
+    pub expr: Option<Expr>,
+    /// The evaluated value: `expr`, or one more than the previous constant.
+    pub value: i64,
+    /// The comment written directly above or trailing the constant.
+    pub doc: Option<String>,
+}
+
+/// A file-mapped variable, e.g. `int32 unk <format=hex>;`, the array
+/// `wchar_t name[0x10];` or the bitfield `uint32 flag : 1;`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Declaration {
+    pub ty: TypeRef,
+    /// `None` only for an unnamed padding bitfield such as `uint32 : 4;`.
+    pub name: Option<String>,
+    /// The arguments passed to a parameterized struct, as in
+    /// `EquipInventoryData inv(0x780, 0x80);`.
+    pub args: Vec<Expr>,
+    /// The bounds of an array such as `char name[4][LEN]`, outermost first.
+    /// An unsized `[]` bound is `None`.
+    pub dimensions: Vec<Option<Expr>>,
+    /// The width of a bitfield such as `uint32 flag : 1;`, in bits.
+    pub bitfield: Option<Expr>,
+    pub attributes: Vec<Attribute>,
+    /// The comment written directly above or trailing the declaration.
+    pub doc: Option<String>,
+}
+
+/// How a [`Variable`] is stored. Neither kind is read from the file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Storage {
+    /// `local`, a variable the template can assign to.
+    Local,
+    /// `const`, a named constant that must be initialized.
+    Const,
+}
+
+/// A variable that is not mapped onto the file, such as
+/// `local int i = 0, j;` or `const uint MAGIC = 0x4D534742;`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Variable {
+    pub storage: Storage,
+    pub ty: TypeRef,
+    /// One entry per name, in source order.
+    pub declarators: Vec<Declarator>,
+    /// The comment written directly above or trailing the declaration.
+    pub doc: Option<String>,
+}
+
+/// One name declared by a [`Variable`], e.g. `c = a + 2` or `buf[16]`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Declarator {
+    pub name: String,
+    /// The bounds of an array, outermost first. An unsized `[]` is `None`.
+    pub dimensions: Vec<Option<Expr>>,
+    pub init: Option<Expr>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConditionalKeyword {
+    If,
+    ElseIf,
+}
+
+/// A `case value:` or `default:` label of a switch.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CaseLabel {
+    Case(Expr),
+    Default,
+}
+
+/// A switch label and the statements up to the next label. Without a
+/// `break`, execution falls through to the next case.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SwitchCase {
+    pub label: CaseLabel,
+    pub body: Vec<Statement>,
+}
+
+/// The `if (...)` or `else if (...)` header of a conditional block.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConditionalHeader {
+    pub keyword: ConditionalKeyword,
+    pub condition: Expr,
+}
+
+/// `if (condition) body`, followed by any `else if` branches and an
+/// optional final `else`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IfStatement {
+    pub condition: Expr,
+    pub body: Vec<Statement>,
+    pub else_ifs: Vec<ElseIf>,
+    pub else_body: Option<Vec<Statement>>,
+}
+
+/// An `else if (condition) body` branch of an [`IfStatement`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ElseIf {
+    pub condition: Expr,
+    pub body: Vec<Statement>,
+}
+
+/// `while (condition) body`
+#[derive(Debug, Clone, PartialEq)]
+pub struct WhileStatement {
+    pub condition: Expr,
+    pub body: Vec<Statement>,
+}
+
+/// `do body while (condition);`
+#[derive(Debug, Clone, PartialEq)]
+pub struct DoWhileStatement {
+    pub body: Vec<Statement>,
+    pub condition: Expr,
+}
+
+/// `for ([init]; [condition]; [step]) body`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForStatement {
+    pub init: Option<ForInit>,
+    pub condition: Option<Expr>,
+    pub step: Option<Expr>,
+    pub body: Vec<Statement>,
+}
+
+/// The first clause of a `for` loop, either a variable such as
+/// `local int i = 0` or an expression such as `i = 0`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ForInit {
+    Variable(Variable),
+    Expression(Expr),
+}
+
+/// `switch (value) { cases }`
+#[derive(Debug, Clone, PartialEq)]
+pub struct SwitchStatement {
+    pub value: Expr,
+    pub cases: Vec<SwitchCase>,
+}
+
+/// A literal constant, decoded to its value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Literal {
+    Int(i64),
+    /// An integer with a `u` suffix, or one too large for an `i64`.
+    UInt(u64),
+    Float(f64),
+    /// A character constant; multi-character constants such as `'RIFF'`
+    /// hold their bytes packed big-endian.
+    Char(u64),
+    String(String),
+    /// A wide `L"..."` string.
+    WideString(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum Expr {
     Identifier(String),
-    Literal(i32),
+    Literal(Literal),
     FunctionCall {
         name: String,
         args: Vec<Expr>,
@@ -14,9 +360,56 @@ pub enum Expr {
         right: Box<Expr>,
     },
     Parens(Box<Expr>),
+    /// A prefix operator: `!`, `~`, `-`, `+`, `++` or `--`.
+    UnaryOp {
+        op: Expression,
+        operand: Box<Expr>,
+    },
+    /// A postfix `++` or `--`.
+    PostfixOp {
+        op: Expression,
+        operand: Box<Expr>,
+    },
+    Ternary {
+        condition: Box<Expr>,
+        then_branch: Box<Expr>,
+        else_branch: Box<Expr>,
+    },
+    Cast {
+        ty: TypeRef,
+        expr: Box<Expr>,
+    },
+    Member {
+        object: Box<Expr>,
+        member: String,
+    },
+    Index {
+        object: Box<Expr>,
+        index: Box<Expr>,
+    },
+    /// `target = value` or a compound assignment such as `target += value`.
+    Assign {
+        target: Box<Expr>,
+        op: Expression,
+        value: Box<Expr>,
+    },
+    /// Expressions separated by the comma operator, evaluated left to right.
+    Comma(Vec<Expr>),
 }
 
-#[derive(Debug, PartialEq)]
+impl Expr {
+    /// Whether the expression names storage that can be assigned to: an
+    /// identifier, member access or index, optionally parenthesized.
+    pub fn is_lvalue(&self) -> bool {
+        match self {
+            Expr::Identifier(_) | Expr::Member { .. } | Expr::Index { .. } => true,
+            Expr::Parens(inner) => inner.is_lvalue(),
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum Expression {
     And,
     Or,
@@ -51,6 +444,7 @@ pub enum Expression {
     BinaryOrEquals,
     BinaryShiftLeftEquals,
     BinaryShiftRightEquals,
+    Assign,
 }
 
 impl Expression {
@@ -79,6 +473,7 @@ impl Expression {
             Self::BinaryOr => "|",
             // =
             Self::Equals => "==",
+            Self::Assign => "=",
             // +
             Self::AddEquals => "+=",
             Self::Increment => "++",
@@ -102,7 +497,6 @@ impl Expression {
             // special
             Self::BinaryInvert => "~",
             Self::Ternary => "?:",
-           
         }
     }
 
@@ -142,12 +536,59 @@ impl Expression {
             BinaryOrEquals,
             BinaryShiftLeftEquals,
             BinaryShiftRightEquals,
+            Assign,
         ]
     }
 
+    /// Looks up the operator spelled exactly as `token`.
+    pub fn from_token(token: &str) -> Option<Self> {
+        Self::variants()
+            .iter()
+            .find(|expr| expr.to_str() == token)
+            .cloned()
+    }
+
+    /// Binding power of the operator between two operands, from `||`
+    /// (loosest) to `*` (tightest). `None` if it is not a binary operator.
+    pub fn precedence(&self) -> Option<u8> {
+        match self {
+            Self::Or => Some(1),
+            Self::And => Some(2),
+            Self::BinaryOr => Some(3),
+            Self::BinaryXor => Some(4),
+            Self::BinaryAnd => Some(5),
+            Self::Equals | Self::NotEquals => Some(6),
+            Self::GreaterThan
+            | Self::LessThan
+            | Self::GreaterThanOrEqualTo
+            | Self::LessThanOrEqualTo => Some(7),
+            Self::BinaryShiftLeft | Self::BinaryShiftRight => Some(8),
+            Self::Add | Self::Subtract => Some(9),
+            Self::Multiply | Self::Divide | Self::Modulus => Some(10),
+            _ => None,
+        }
+    }
+
+    /// Whether this is `=` or one of the compound assignments such as `+=`.
+    pub fn is_assignment(&self) -> bool {
+        matches!(
+            self,
+            Self::Assign
+                | Self::AddEquals
+                | Self::MinusEquals
+                | Self::MultiplyEquals
+                | Self::DivideEquals
+                | Self::BinaryAndEquals
+                | Self::BinaryXorEquals
+                | Self::BinaryModulusEquals
+                | Self::BinaryOrEquals
+                | Self::BinaryShiftLeftEquals
+                | Self::BinaryShiftRightEquals
+        )
+    }
+
     pub fn all_expressions() -> Vec<&'static str> {
         Self::variants().iter().map(|expr| expr.to_str()).collect()
-
     }
 }
 
@@ -156,43 +597,3 @@ impl InputLength for Expression {
         self.to_str().len()
     }
 }
-
-impl Clone for Expression {
-    fn clone(&self) -> Self {
-        match self {
-            Self::And => Self::And,
-            Self::Or => Self::Or,
-            Self::Not => Self::Not,
-            Self::Equals => Self::Equals,
-            Self::NotEquals => Self::NotEquals,
-            Self::GreaterThan => Self::GreaterThan,
-            Self::LessThan => Self::LessThan,
-            Self::GreaterThanOrEqualTo => Self::GreaterThanOrEqualTo,
-            Self::LessThanOrEqualTo => Self::LessThanOrEqualTo,
-            Self::Add => Self::Add,
-            Self::Subtract => Self::Subtract,
-            Self::Multiply => Self::Multiply,
-            Self::Divide => Self::Divide,
-            Self::BinaryInvert => Self::BinaryInvert,
-            Self::BinaryXor => Self::BinaryXor,
-            Self::BinaryAnd => Self::BinaryAnd,
-            Self::BinaryOr => Self::BinaryOr,
-            Self::Modulus => Self::Modulus,
-            Self::Increment => Self::Increment,
-            Self::Decrement => Self::Decrement,
-            Self::Ternary => Self::Ternary,
-            Self::BinaryShiftLeft => Self::BinaryShiftLeft,
-            Self::BinaryShiftRight => Self::BinaryShiftRight,
-            Self::AddEquals => Self::AddEquals,
-            Self::MinusEquals => Self::MinusEquals,
-            Self::MultiplyEquals => Self::MultiplyEquals,
-            Self::DivideEquals => Self::DivideEquals,
-            Self::BinaryAndEquals => Self::BinaryAndEquals,
-            Self::BinaryXorEquals => Self::BinaryXorEquals,
-            Self::BinaryModulusEquals => Self::BinaryModulusEquals,
-            Self::BinaryOrEquals => Self::BinaryOrEquals,
-            Self::BinaryShiftLeftEquals => Self::BinaryShiftLeftEquals,
-            Self::BinaryShiftRightEquals => Self::BinaryShiftRightEquals,
-        }
-    }
-}
@@ -0,0 +1,98 @@
+use std::fmt::{self, Display, Formatter};
+
+use nom::{
+    error::{VerboseError, VerboseErrorKind},
+    IResult,
+};
+
+/// The result type shared by every parser in the crate. Errors carry the
+/// `context` labels they passed through, innermost first.
+pub type ParseResult<'a, O> = IResult<&'a str, O, VerboseError<&'a str>>;
+
+/// Aborts parsing at `input` with `message`, without letting an enclosing
+/// `alt` backtrack into another branch.
+pub fn failure<'a, O>(input: &'a str, message: &'static str) -> ParseResult<'a, O> {
+    Err(nom::Err::Failure(VerboseError {
+        errors: vec![(input, VerboseErrorKind::Context(message))],
+    }))
+}
+
+/// An error produced while parsing a whole template, pointing at the
+/// 1-based line and column where parsing stopped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    /// Builds an error for the position of `remaining` within `source`.
+    ///
+    /// `remaining` must be a suffix of `source`, which is what every nom
+    /// parser hands back as its unconsumed input.
+    pub fn at(source: &str, remaining: &str, message: impl Into<String>) -> Self {
+        let offset = source.len().saturating_sub(remaining.len());
+        let consumed = &source[..offset];
+        let line = consumed.matches('\n').count() + 1;
+        let column = match consumed.rfind('\n') {
+            Some(index) => consumed[index + 1..].chars().count() + 1,
+            None => consumed.chars().count() + 1,
+        };
+        ParseError {
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+
+    /// Builds an error from a parser failure raised with [`failure`] or
+    /// `cut`, reporting its innermost position and context label.
+    pub fn from_failure(source: &str, error: &VerboseError<&str>) -> Self {
+        let remaining = error.errors.first().map_or(source, |(input, _)| *input);
+        let message = error
+            .errors
+            .iter()
+            .find_map(|(_, kind)| match kind {
+                VerboseErrorKind::Context(context) => Some(*context),
+                _ => None,
+            })
+            .unwrap_or("invalid syntax");
+        ParseError::at(source, remaining, message)
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[cfg(test)]
+mod parse_error_tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_parse_error_position1() {
+        let source = "int a;\nint b;\n  oops";
+        let remaining = &source[source.len() - 4..];
+        let error = ParseError::at(source, remaining, "unexpected input");
+        assert_eq!(error.line, 3);
+        assert_eq!(error.column, 3);
+        assert_eq!(error.to_string(), "line 3, column 3: unexpected input");
+    }
+
+    #[test]
+    fn test_parse_error_position2() {
+        let source = "oops";
+        let error = ParseError::at(source, source, "unexpected input");
+        assert_eq!((error.line, error.column), (1, 1));
+    }
+}
@@ -0,0 +1,142 @@
+use std::collections::HashMap;
+
+use crate::ast::{Expr, Expression, Literal};
+
+/// Truncates `value` to the width of a built-in integer type, the way a cast
+/// such as `(uchar)0x1FF` would. Other types leave the value unchanged.
+fn cast(type_name: &str, value: i64) -> i64 {
+    match type_name {
+        "char" | "byte" | "CHAR" | "BYTE" | "int8" => value as i8 as i64,
+        "uchar" | "ubyte" | "UCHAR" | "UBYTE" | "uint8" => value as u8 as i64,
+        "short" | "int16" | "SHORT" | "INT16" => value as i16 as i64,
+        "ushort" | "uint16" | "USHORT" | "UINT16" | "WORD" => value as u16 as i64,
+        "int" | "int32" | "long" | "INT" | "INT32" | "LONG" => value as i32 as i64,
+        "uint" | "uint32" | "ulong" | "UINT" | "UINT32" | "ULONG" | "DWORD" => value as u32 as i64,
+        _ => value,
+    }
+}
+
+fn binary(op: &Expression, left: i64, right: i64) -> Result<i64, &'static str> {
+    let value = match op {
+        Expression::Add => left.wrapping_add(right),
+        Expression::Subtract => left.wrapping_sub(right),
+        Expression::Multiply => left.wrapping_mul(right),
+        Expression::Divide | Expression::Modulus if right == 0 => {
+            return Err("division by zero in constant expression")
+        }
+        Expression::Divide => left.wrapping_div(right),
+        Expression::Modulus => left.wrapping_rem(right),
+        Expression::BinaryAnd => left & right,
+        Expression::BinaryOr => left | right,
+        Expression::BinaryXor => left ^ right,
+        Expression::BinaryShiftLeft => left.wrapping_shl(right as u32),
+        Expression::BinaryShiftRight => left.wrapping_shr(right as u32),
+        Expression::Equals => (left == right) as i64,
+        Expression::NotEquals => (left != right) as i64,
+        Expression::LessThan => (left < right) as i64,
+        Expression::GreaterThan => (left > right) as i64,
+        Expression::LessThanOrEqualTo => (left <= right) as i64,
+        Expression::GreaterThanOrEqualTo => (left >= right) as i64,
+        _ => return Err("operator not allowed in constant expression"),
+    };
+    Ok(value)
+}
+
+/// Evaluates an integer constant expression such as `FLAG_A | 1 << 4`.
+///
+/// Identifiers are looked up in `constants`, e.g. the enum constants
+/// declared before the one being evaluated. Unsigned literals and character
+/// constants are reinterpreted as `i64`.
+///
+/// # Example
+///
+/// ```
+/// use std::collections::HashMap;
+/// use bt_parser::{eval::eval_constant, parse_nested::expression};
+///
+/// let constants = HashMap::from([("BASE".to_string(), 0x10)]);
+/// let (_, expr) = expression("(BASE << 4) + 'A'").unwrap();
+/// assert_eq!(eval_constant(&expr, &constants), Ok(0x141));
+/// ```
+pub fn eval_constant(expr: &Expr, constants: &HashMap<String, i64>) -> Result<i64, &'static str> {
+    let eval = |expr: &Expr| eval_constant(expr, constants);
+    match expr {
+        Expr::Literal(Literal::Int(value)) => Ok(*value),
+        Expr::Literal(Literal::UInt(value)) | Expr::Literal(Literal::Char(value)) => {
+            Ok(*value as i64)
+        }
+        Expr::Literal(_) => Err("expected an integer constant"),
+        Expr::Identifier(name) => constants
+            .get(name)
+            .copied()
+            .ok_or("unknown identifier in constant expression"),
+        Expr::Parens(inner) => eval(inner),
+        Expr::Cast { ty, expr } => Ok(cast(&ty.name, eval(expr)?)),
+        Expr::UnaryOp { op, operand } => {
+            let operand = eval(operand)?;
+            match op {
+                Expression::Subtract => Ok(operand.wrapping_neg()),
+                Expression::Add => Ok(operand),
+                Expression::Not => Ok((operand == 0) as i64),
+                Expression::BinaryInvert => Ok(!operand),
+                _ => Err("operator not allowed in constant expression"),
+            }
+        }
+        Expr::BinaryOp { left, op, right } => match op {
+            Expression::And => Ok((eval(left)? != 0 && eval(right)? != 0) as i64),
+            Expression::Or => Ok((eval(left)? != 0 || eval(right)? != 0) as i64),
+            _ => binary(op, eval(left)?, eval(right)?),
+        },
+        Expr::Ternary {
+            condition,
+            then_branch,
+            else_branch,
+        } => match eval(condition)? {
+            0 => eval(else_branch),
+            _ => eval(then_branch),
+        },
+        _ => Err("not a constant expression"),
+    }
+}
+
+#[cfg(test)]
+mod eval_tests {
+    use super::*;
+    use crate::parse_nested::expression;
+
+    fn eval(input: &str) -> Result<i64, &'static str> {
+        let constants = HashMap::from([("A".to_string(), 3), ("B".to_string(), -1)]);
+        eval_constant(&expression(input).unwrap().1, &constants)
+    }
+
+    #[test]
+    fn test_eval_constant_arithmetic() {
+        assert_eq!(eval("1 + 2 * 3"), Ok(7));
+        assert_eq!(eval("(1 + 2) * 3"), Ok(9));
+        assert_eq!(eval("-A % 2"), Ok(-1));
+        assert_eq!(eval("1 << 31"), Ok(0x80000000));
+        assert_eq!(eval("0xffffffffffffffff"), Ok(-1));
+        assert_eq!(eval("~0 & 0xff"), Ok(0xff));
+        assert_eq!(eval("A > 2 && B < 0 ? 10 : 20"), Ok(10));
+        assert_eq!(eval("!A || 0"), Ok(0));
+        assert_eq!(eval("(uchar)0x1ff"), Ok(0xff));
+        assert_eq!(eval("(char)0xff"), Ok(-1));
+        assert_eq!(eval("'RIFF'"), Ok(0x52494646));
+    }
+
+    #[test]
+    fn test_eval_constant_errors() {
+        assert_eq!(
+            eval("C + 1"),
+            Err("unknown identifier in constant expression")
+        );
+        assert_eq!(
+            eval("1 / (A - 3)"),
+            Err("division by zero in constant expression")
+        );
+        assert_eq!(eval("1.5"), Err("expected an integer constant"));
+        assert_eq!(eval("f(1)"), Err("not a constant expression"));
+        assert_eq!(eval("A++"), Err("not a constant expression"));
+        assert_eq!(eval("0 && 1 / 0"), Ok(0));
+    }
+}
@@ -1,5 +1,13 @@
+pub mod analysis;
 pub mod ast;
+pub mod error;
+pub mod eval;
+pub mod metadata;
 pub mod parse_nested;
 pub mod parsing;
-pub mod types;
+pub mod registry;
 pub mod shared;
+pub mod template;
+
+pub use error::ParseError;
+pub use template::parse_template;
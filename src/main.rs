@@ -1,22 +1,76 @@
-use std::fs::read_to_string;
+use std::{
+    fs::{read_to_string, File},
+    io::Read,
+    path::PathBuf,
+    process::exit,
+};
 
-use bt_parser::parsing::comment_line::comment_line;
+use bt_parser::{analysis::analyze, parse_template, registry::TemplateRegistry};
 
-pub fn main() {
-    let path = "D:/Elden Ring Tools/EldenRingSaveTemplate-master/SL2.bt";
-    let input = read_to_string(path).unwrap();
-    let (input, _) = comment_line(input.as_str()).unwrap();
-    let (input, _) = comment_line(input).unwrap();
-    let (input, _) = comment_line(input).unwrap();
-    let (input, _) = comment_line(input).unwrap();
-    let (input, _) = comment_line(input).unwrap();
-    let (input, _) = comment_line(input).unwrap();
-    let (input, _) = comment_line(input).unwrap();
-    let (input, _) = comment_line(input).unwrap();
-    let (input, _) = comment_line(input).unwrap();
-    let (input, _) = comment_line(input).unwrap();
-    let (input, _) = comment_line(input).unwrap();
-    let (input, _) = comment_line(input).unwrap();
+/// Picks the best template in `dir` for the binary file at `data`.
+fn select_template(dir: &PathBuf, data: &PathBuf) -> PathBuf {
+    let (registry, errors) = TemplateRegistry::load_dir(dir).unwrap_or_else(|error| {
+        eprintln!("error: {}", error);
+        exit(1);
+    });
+    for error in errors {
+        eprintln!("warning: skipping {}", error);
+    }
+    let mut leading_bytes = Vec::new();
+    File::open(data)
+        .and_then(|file| {
+            file.take(registry.signature_len() as u64)
+                .read_to_end(&mut leading_bytes)
+        })
+        .unwrap_or_else(|error| {
+            eprintln!("error: {}: {}", data.display(), error);
+            exit(1);
+        });
+    match registry.select(data, &leading_bytes).first() {
+        Some(template) => template.path.clone(),
+        None => {
+            eprintln!(
+                "error: no template in {} matches {}",
+                dir.display(),
+                data.display()
+            );
+            exit(1);
+        }
+    }
+}
 
-    println!("input: {:?}", input);
+pub fn main() {
+    let mut args = std::env::args().skip(1).map(PathBuf::from);
+    let path = match (args.next(), args.next()) {
+        (Some(dir), Some(data)) if dir.is_dir() => select_template(&dir, &data),
+        (Some(path), None) => path,
+        _ => {
+            eprintln!("usage: bt_parser <template.bt> | <template dir> <data file>");
+            exit(2);
+        }
+    };
+    let input = read_to_string(&path).unwrap_or_else(|error| {
+        eprintln!("error: {}: {}", path.display(), error);
+        exit(1);
+    });
+    let template = parse_template(input.as_str()).unwrap_or_else(|error| {
+        eprintln!("error: {}", error);
+        exit(1);
+    });
+    for warning in &template.warnings {
+        eprintln!("warning: {}", warning);
+    }
+    let errors = analyze(&template);
+    for error in &errors {
+        eprintln!("error: {}", error);
+    }
+    if let Some(metadata) = template.metadata {
+        println!("{:?}", metadata);
+    }
+    for item in template.items {
+        println!("{:?}", item);
+    }
+    if !errors.is_empty() {
+        exit(1);
+    }
 }
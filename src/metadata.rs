@@ -0,0 +1,339 @@
+use crate::{error::ParseError, shared::glob::Glob};
+
+/// The entries of the standard 010 Editor header banner at the top of a
+/// template, e.g. `//  File Mask: *.sl2`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TemplateMetadata {
+    pub file: Option<String>,
+    pub authors: Vec<String>,
+    pub version: Option<String>,
+    pub purpose: Option<String>,
+    pub category: Option<String>,
+    pub file_masks: Vec<Glob>,
+    /// Alternative signatures; a file matches if any one of them does.
+    pub id_bytes: Vec<BytePattern>,
+    /// One entry per version, continuation lines joined onto it.
+    pub history: Vec<String>,
+}
+
+/// An `ID Bytes` signature such as `[+4] 66 74 79 70` or `50 4B ?? ??`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BytePattern {
+    /// Where in the file the signature starts.
+    pub offset: usize,
+    /// The expected bytes, `None` for a `??` wildcard.
+    pub bytes: Vec<Option<u8>>,
+}
+
+impl BytePattern {
+    /// Parses one signature, returning `None` if it is malformed.
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        let (offset, text) = match text.strip_prefix('[') {
+            Some(rest) => {
+                let (offset, rest) = rest.split_once(']')?;
+                let offset = offset.trim().trim_start_matches('+');
+                let offset = match offset.strip_prefix("0x") {
+                    Some(hex) => usize::from_str_radix(hex, 16).ok()?,
+                    None => offset.parse().ok()?,
+                };
+                (offset, rest)
+            }
+            None => (0, text),
+        };
+        let mut bytes = Vec::new();
+        for token in text.split_whitespace() {
+            if !token.is_ascii() || token.len() % 2 != 0 {
+                return None;
+            }
+            for index in (0..token.len()).step_by(2) {
+                bytes.push(match &token[index..index + 2] {
+                    "??" => None,
+                    hex => Some(u8::from_str_radix(hex, 16).ok()?),
+                });
+            }
+        }
+        if bytes.is_empty() {
+            return None;
+        }
+        Some(BytePattern { offset, bytes })
+    }
+
+    /// The number of non-wildcard bytes, used to rank competing signatures.
+    pub fn specificity(&self) -> usize {
+        self.bytes.iter().filter(|byte| byte.is_some()).count()
+    }
+
+    /// How many leading bytes of a file are needed to check this signature.
+    pub fn end(&self) -> usize {
+        self.offset + self.bytes.len()
+    }
+
+    /// Whether `data`, the leading bytes of a file, carries this signature.
+    pub fn matches(&self, data: &[u8]) -> bool {
+        data.get(self.offset..self.end()).is_some_and(|window| {
+            window
+                .iter()
+                .zip(&self.bytes)
+                .all(|(byte, expected)| expected.is_none() || *expected == Some(*byte))
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    File,
+    Authors,
+    Version,
+    Purpose,
+    Category,
+    FileMask,
+    IdBytes,
+    History,
+}
+
+impl Field {
+    fn from_key(key: &str) -> Option<Self> {
+        match key.trim().to_ascii_lowercase().as_str() {
+            "file" => Some(Field::File),
+            "author" | "authors" => Some(Field::Authors),
+            "version" => Some(Field::Version),
+            "purpose" => Some(Field::Purpose),
+            "category" => Some(Field::Category),
+            "file mask" => Some(Field::FileMask),
+            "id bytes" => Some(Field::IdBytes),
+            "history" => Some(Field::History),
+            _ => None,
+        }
+    }
+}
+
+/// A header entry: its field, the input from its line onwards for error
+/// positions, and its value lines.
+struct Entry<'a> {
+    field: Field,
+    position: &'a str,
+    lines: Vec<&'a str>,
+}
+
+/// The comment text of each line in the comment block at the start of
+/// `input`, paired with the input from that line onwards.
+fn header_lines(input: &str) -> Vec<(&str, &str)> {
+    let input_start = input.as_ptr() as usize;
+    let mut lines = Vec::new();
+    let mut in_block = false;
+    for line in input.lines().skip_while(|line| line.trim().is_empty()) {
+        let position = &input[line.as_ptr() as usize - input_start..];
+        let trimmed = line.trim();
+        let text = if in_block {
+            trimmed
+        } else if let Some(text) = trimmed.strip_prefix("//") {
+            lines.push((text, position));
+            continue;
+        } else if let Some(text) = trimmed.strip_prefix("/*") {
+            in_block = true;
+            text
+        } else {
+            break;
+        };
+        let text = match text.split_once("*/") {
+            Some((text, _)) => {
+                in_block = false;
+                text
+            }
+            None => text,
+        };
+        lines.push((text.strip_prefix('*').unwrap_or(text), position));
+    }
+    lines
+}
+
+fn joined(entry: &Entry) -> String {
+    entry.lines.join(" ")
+}
+
+fn split_list<'a>(text: &'a str, separators: &'a [char]) -> impl Iterator<Item = &'a str> {
+    text.split(separators)
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+}
+
+/// Reads the header banner from the leading comments of a template.
+///
+/// Only the comment block at the very start of `input` is examined, so this
+/// is cheap enough to run over a whole directory of templates. Returns
+/// `Ok(None)` when the block has no recognized entries, and an error pointing
+/// at the `ID Bytes` line when a signature cannot be decoded.
+///
+/// # Example
+///
+/// ```
+/// use bt_parser::metadata::{parse_metadata, BytePattern};
+///
+/// let input = "//------\n//      File: SL2.bt\n// File Mask: *.sl2\n//  ID Bytes: 42 4E 44 34\n//------\n";
+/// let metadata = parse_metadata(input).unwrap().unwrap();
+/// assert_eq!(metadata.file.as_deref(), Some("SL2.bt"));
+/// assert!(metadata.file_masks[0].matches("ER0000.sl2"));
+/// assert_eq!(metadata.id_bytes, vec![BytePattern::parse("42 4E 44 34").unwrap()]);
+/// ```
+pub fn parse_metadata(input: &str) -> Result<Option<TemplateMetadata>, ParseError> {
+    let mut entries: Vec<Entry> = Vec::new();
+    let mut open = false;
+    for (text, position) in header_lines(input) {
+        let text = text.trim();
+        if !text.is_empty() && text.chars().all(|c| matches!(c, '-' | '=' | '*')) {
+            open = false;
+            continue;
+        }
+        let field = text
+            .split_once(':')
+            .and_then(|(key, value)| Some((Field::from_key(key)?, value.trim())));
+        if let Some((field, value)) = field {
+            let lines = if value.is_empty() {
+                vec![]
+            } else {
+                vec![value]
+            };
+            entries.push(Entry {
+                field,
+                position,
+                lines,
+            });
+            open = true;
+        } else if let Some(entry) = entries.last_mut().filter(|_| open && !text.is_empty()) {
+            entry.lines.push(text);
+        }
+    }
+    if entries.is_empty() {
+        return Ok(None);
+    }
+
+    let mut metadata = TemplateMetadata::default();
+    for entry in &entries {
+        let value = Some(joined(entry)).filter(|value| !value.is_empty());
+        match entry.field {
+            Field::File => metadata.file = value,
+            Field::Version => metadata.version = value,
+            Field::Purpose => metadata.purpose = value,
+            Field::Category => metadata.category = value,
+            Field::Authors => {
+                metadata.authors = split_list(&joined(entry), &[',']).map(Into::into).collect()
+            }
+            Field::FileMask => {
+                metadata.file_masks = split_list(&joined(entry), &[',', ';'])
+                    .map(Glob::new)
+                    .collect();
+            }
+            Field::IdBytes => {
+                metadata.id_bytes = split_list(&joined(entry), &[','])
+                    .map(BytePattern::parse)
+                    .collect::<Option<_>>()
+                    .ok_or_else(|| {
+                        ParseError::at(input, entry.position, "invalid ID Bytes signature")
+                    })?;
+            }
+            Field::History => {
+                for line in &entry.lines {
+                    match metadata.history.last_mut() {
+                        Some(last) if !line.starts_with(|c: char| c.is_ascii_digit()) => {
+                            last.push(' ');
+                            last.push_str(line);
+                        }
+                        _ => metadata.history.push(line.to_string()),
+                    }
+                }
+            }
+        }
+    }
+    Ok(Some(metadata))
+}
+
+#[cfg(test)]
+mod metadata_tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const HEADER: &str = r#"
+//------------------------------------------------
+//--- 010 Editor v14.0 Binary Template
+//
+//      File: ZIP.bt
+//   Authors: SweetScape Software, Jane Doe
+//   Version: 2.3
+//   Purpose: Parse ZIP archive files, including
+//            self-extracting executables.
+//  Category: Archive
+// File Mask: *.zip;*.jar, *.apk
+//  ID Bytes: 50 4B 03 04, [+4] 66 74 ?? 70
+//   History:
+//   2.3   2020-11-18 SweetScape: Added support
+//         for ZIP64.
+//   2.2   2019-03-01 SweetScape: Fixed typos.
+//------------------------------------------------
+typedef struct {
+} ZIPFILERECORD;
+"#;
+
+    #[test]
+    fn test_parse_metadata1() {
+        let metadata = parse_metadata(HEADER).unwrap().unwrap();
+        assert_eq!(
+            metadata,
+            TemplateMetadata {
+                file: Some("ZIP.bt".into()),
+                authors: vec!["SweetScape Software".into(), "Jane Doe".into()],
+                version: Some("2.3".into()),
+                purpose: Some(
+                    "Parse ZIP archive files, including self-extracting executables.".into()
+                ),
+                category: Some("Archive".into()),
+                file_masks: vec![Glob::new("*.zip"), Glob::new("*.jar"), Glob::new("*.apk")],
+                id_bytes: vec![
+                    BytePattern {
+                        offset: 0,
+                        bytes: vec![Some(0x50), Some(0x4B), Some(0x03), Some(0x04)],
+                    },
+                    BytePattern {
+                        offset: 4,
+                        bytes: vec![Some(0x66), Some(0x74), None, Some(0x70)],
+                    },
+                ],
+                history: vec![
+                    "2.3   2020-11-18 SweetScape: Added support for ZIP64.".into(),
+                    "2.2   2019-03-01 SweetScape: Fixed typos.".into(),
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_metadata_block_comment() {
+        let input = "/*\n * File: SL2.bt\n * Category: Game\n */\nint a;";
+        let metadata = parse_metadata(input).unwrap().unwrap();
+        assert_eq!(metadata.file.as_deref(), Some("SL2.bt"));
+        assert_eq!(metadata.category.as_deref(), Some("Game"));
+    }
+
+    #[test]
+    fn test_parse_metadata_without_header() {
+        assert_eq!(parse_metadata("// just a note\nint a;"), Ok(None));
+        assert_eq!(parse_metadata("int a;\n// File: late.bt"), Ok(None));
+    }
+
+    #[test]
+    fn test_parse_metadata_invalid_id_bytes() {
+        let error = parse_metadata("// File: a.bt\n// ID Bytes: 50 4G\n").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 1));
+        assert_eq!(error.message, "invalid ID Bytes signature");
+    }
+
+    #[test]
+    fn test_byte_pattern_matches() {
+        let pattern = BytePattern::parse("[+2] 4B ??").unwrap();
+        assert!(pattern.matches(b"PK\x4B\x00"));
+        assert!(!pattern.matches(b"PK\x4C\x00"));
+        assert!(!pattern.matches(b"PK\x4B"));
+        assert_eq!(BytePattern::parse("504"), None);
+        assert_eq!(BytePattern::parse(""), None);
+    }
+}
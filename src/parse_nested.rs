@@ -1,366 +1,956 @@
 use nom::{
     branch::alt,
-    bytes::complete::{tag, take_while1},
-    character::complete::{char, multispace0, one_of, satisfy, space0},
-    combinator::{map, map_res, not, opt, peek, recognize, value},
-    error::Error,
-    multi::{many0, many1, separated_list1},
-    sequence::{delimited, pair, preceded, terminated, tuple},
-    AsChar, IResult, Parser,
+    bytes::complete::tag,
+    character::complete::{char, space0},
+    combinator::{map, value, verify},
+    multi::{fold_many0, many0, separated_list0},
+    sequence::{delimited, pair, preceded, terminated},
 };
 
-use crate::ast::Expression;
+pub mod literal;
 
-fn ws<'a, F: 'a, O>(inner: F) -> impl FnMut(&'a str) -> IResult<&'a str, O>
+use crate::{
+    ast::{Expr, Expression},
+    error::{failure, ParseResult},
+    parse_nested::literal::literal,
+    parsing::{
+        comment_line::multispace_or_comment0, statement::identifier,
+        type_specifier::type_specifier, typedef_line::typedef_member::is_typedef_member,
+    },
+};
+
+fn ws<'a, F, O>(inner: F) -> impl FnMut(&'a str) -> ParseResult<'a, O>
 where
-    F: FnMut(&'a str) -> IResult<&'a str, O>,
+    F: FnMut(&'a str) -> ParseResult<'a, O> + 'a,
 {
-    delimited(multispace0, inner, multispace0)
-}
-
-fn identifier(input: &str) -> IResult<&str, &str> {
-    ws(take_while1(|c: char| c.is_ascii_alphanumeric()))(input).map(|(i, o)| (i.trim(), o.trim()))
+    // Only spaces are skipped after a token, so that a comment trailing an
+    // expression on the same line stays in the input.
+    delimited(multispace_or_comment0, inner, space0)
 }
 
 macro_rules! expression_tag {
-    ($expr:expr) => {
-        map(
-            tuple((
-                peek(tuple((
-                    tag($expr.to_str()),
-                    not_expression_tag,
-                ))),
-                tag($expr.to_str()),
-            )),
-            |(matched, _)| {
-                println!("Expression Tag matched {:?} with {:?}", $expr, matched.0);
-                matched.0
-            },
-        )
+    ($variant:expr) => {
+        (tag($variant.to_str()))
     };
 }
 
-#[cfg(test)]
-mod test_macro_expression_tag {
-    use super::*;
+fn number(input: &str) -> ParseResult<'_, Expr> {
+    map(ws(literal), Expr::Literal)(input)
+}
 
-    #[test]
-    fn test_expression_tag1() {
-        Expression::variants().iter().for_each(|expr| {
-            let ignored = vec![
-                Expression::BinaryAnd,
-                Expression::BinaryOr,
-                Expression::Subtract,
-                Expression::Add,
-                Expression::GreaterThan,
-                Expression::LessThan,
-            ];
-            if ignored.into_iter().any(|r| r.to_str() == expr.to_str()) {
-                return;
-            }
-            let tag_on = format!("{}{}", expr.to_str(), expr.to_str());
-            let tag_on = tag_on.as_str();
-            assert!(
-                expression_tag!(expr)(tag_on).is_err(),
-                "Passed on {}",
-                tag_on
-            );
-        });
-    }
+fn function_call(input: &str) -> ParseResult<'_, Expr> {
+    map(pair(ws(identifier), argument_list), |(name, args)| {
+        Expr::FunctionCall {
+            name: name.into(),
+            args,
+        }
+    })(input)
+}
 
-    #[test]
-    fn test_expression_tag2() {
-        Expression::variants().iter().for_each(|expr| {
-            let tag_on = format!("{}", expr.to_str());
-            let tag_on = tag_on.as_str();
-            assert!(
-                expression_tag!(expr)(tag_on).is_ok(),
-                "Failed on {}",
-                tag_on
-            );
-        });
-    }
+fn parens(input: &str) -> ParseResult<'_, Expr> {
+    map(
+        delimited(ws(char('(')), expression, ws(char(')'))),
+        |inner| Expr::Parens(Box::new(inner)),
+    )(input)
 }
 
-macro_rules! expression_tag {
-    ($variant:expr) => {
-        (tag($variant.to_str()))
-    };
+fn primary(input: &str) -> ParseResult<'_, Expr> {
+    alt((
+        number,
+        function_call,
+        map(ws(identifier), |name| Expr::Identifier(name.into())),
+        parens,
+    ))(input)
+}
+
+enum Postfix {
+    Index(Expr),
+    Member(String),
+    Operator(Expression),
 }
 
-macro_rules! alternating_expression_tags {
-    () => {
+fn postfix_operator(input: &str) -> ParseResult<'_, Postfix> {
+    preceded(
+        multispace_or_comment0,
         alt((
-            alt((
-                expression_tag!(Expression::BinaryShiftLeftEquals),
-                alt((
-                    expression_tag!(Expression::BinaryShiftLeft),
-                    expression_tag!(Expression::LessThanOrEqualTo),
-                )),
-                expression_tag!(Expression::LessThan),
-            )),
-            alt((
-                expression_tag!(Expression::BinaryShiftRightEquals),
-                alt((
-                    expression_tag!(Expression::BinaryShiftRight),
-                    expression_tag!(Expression::GreaterThanOrEqualTo),
-                )),
-                expression_tag!(Expression::GreaterThan),
-            )),
-            alt((
-                alt((
-                    expression_tag!(Expression::And),
-                    expression_tag!(Expression::BinaryAndEquals),
-                )),
-                expression_tag!(Expression::BinaryAnd),
-            )),
-            alt((
-                expression_tag!(Expression::NotEquals),
-                expression_tag!(Expression::Not),
-            )),
-            alt((
-                alt((
-                    expression_tag!(Expression::Or),
-                    expression_tag!(Expression::BinaryOrEquals),
-                )),
-                expression_tag!(Expression::BinaryOr),
-            )),
-            alt((
-                expression_tag!(Expression::Equals),
-                alt((
+            map(
+                delimited(ws(char('[')), expression, char(']')),
+                Postfix::Index,
+            ),
+            map(preceded(char('.'), ws(identifier)), |member| {
+                Postfix::Member(member.into())
+            }),
+            map(
+                terminated(
                     alt((
-                        alt((
-                            expression_tag!(Expression::AddEquals),
+                        value(
+                            Expression::Increment,
                             expression_tag!(Expression::Increment),
-                        )),
-                        expression_tag!(Expression::Add),
-                    )),
-                    alt((
-                        alt((
-                            expression_tag!(Expression::MinusEquals),
+                        ),
+                        value(
+                            Expression::Decrement,
                             expression_tag!(Expression::Decrement),
-                        )),
-                        expression_tag!(Expression::Subtract),
-                    )),
-                    alt((
-                        expression_tag!(Expression::MultiplyEquals),
-                        expression_tag!(Expression::Multiply),
-                    )),
-                    alt((
-                        expression_tag!(Expression::BinaryXorEquals),
-                        expression_tag!(Expression::BinaryXor),
+                        ),
                     )),
-                    alt((
-                        expression_tag!(Expression::BinaryModulusEquals),
-                        expression_tag!(Expression::Modulus),
-                    )),
-                    alt((
-                        expression_tag!(Expression::DivideEquals),
-                        expression_tag!(Expression::Divide),
-                    )),
-                    alt((
-                        expression_tag!(Expression::Ternary),
-                        expression_tag!(Expression::BinaryInvert),
-                    )),
-                )),
-            )),
-        ))
-    };
-}
-
-fn operator(input: &str) -> IResult<&str, &str> {
-    delimited(
-        space0,
-        tuple((
-            alternating_expression_tags!(),
-            not_expression_tag,
+                    not_operand,
+                ),
+                Postfix::Operator,
+            ),
         )),
-        space0,
     )(input)
-    .map(|(i, o): (&str, (&str, ()))| (i.trim(), o.0.trim()))
 }
 
-#[cfg(test)]
-mod test_operator {
-    use super::*;
-
-    #[test]
-    fn test_operator1() {
-        Expression::variants().iter().for_each(|expr| {
-            let ignored = vec![
-                Expression::BinaryAnd,
-                Expression::BinaryOr,
-                Expression::Subtract,
-                Expression::Add,
-                Expression::GreaterThan,
-                Expression::LessThan,
-            ];
-            if ignored.into_iter().any(|r| r.to_str() == expr.to_str()) {
-                return;
-            }
-            let tag_on = format!("{}{}", expr.to_str(), expr.to_str());
-            let tag_on = tag_on.as_str();
-            assert!(operator(tag_on).is_err(), "Passed on {}", tag_on);
-        });
+/// Fails where an operand starts, so the `--` in `a--b` is a subtraction
+/// followed by a negation rather than a postfix decrement.
+fn not_operand(input: &str) -> ParseResult<'_, ()> {
+    match starts_operand(input) {
+        true => Err(nom::Err::Error(nom::error_position!(
+            input,
+            nom::error::ErrorKind::Not
+        ))),
+        false => Ok((input, ())),
     }
+}
 
-    #[test]
-    fn test_operator2() {
-        Expression::variants().iter().for_each(|expr| {
-            let tag_on = format!("{}", expr.to_str());
-            let tag_on = tag_on.as_str();
-            assert!(operator(tag_on).is_ok(), "Failed on {}", tag_on);
-        });
-    }
+fn postfix(input: &str) -> ParseResult<'_, Expr> {
+    let (input, operand) = primary(input)?;
+    fold_many0(
+        postfix_operator,
+        move || operand.clone(),
+        |operand, postfix| match postfix {
+            Postfix::Index(index) => Expr::Index {
+                object: Box::new(operand),
+                index: Box::new(index),
+            },
+            Postfix::Member(member) => Expr::Member {
+                object: Box::new(operand),
+                member,
+            },
+            Postfix::Operator(op) => Expr::PostfixOp {
+                op,
+                operand: Box::new(operand),
+            },
+        },
+    )(input)
 }
 
-fn not_expression_tag(input: &str) -> IResult<&str, ()> {
-    not(alternating_expression_tags!())(input).map(|(i, _)| {
-        return (i.trim(), ());
-    })
+fn prefix_operator(input: &str) -> ParseResult<'_, Expression> {
+    preceded(
+        multispace_or_comment0,
+        alt((
+            value(
+                Expression::Increment,
+                expression_tag!(Expression::Increment),
+            ),
+            value(
+                Expression::Decrement,
+                expression_tag!(Expression::Decrement),
+            ),
+            value(Expression::Not, expression_tag!(Expression::Not)),
+            value(
+                Expression::BinaryInvert,
+                expression_tag!(Expression::BinaryInvert),
+            ),
+            value(Expression::Subtract, expression_tag!(Expression::Subtract)),
+            value(Expression::Add, expression_tag!(Expression::Add)),
+        )),
+    )(input)
 }
 
-#[cfg(test)]
-mod test_not_expression_tags {
-    use super::*;
+/// Whether an operand starts here, e.g. a name, a number, a literal, a `(`
+/// or a `!` or `~` applied to one.
+fn starts_operand(input: &str) -> bool {
+    matches!(
+        input.trim_start().chars().next(),
+        Some(c) if c.is_alphanumeric() || matches!(c, '_' | '(' | '"' | '\'' | '!' | '~')
+    )
+}
 
-    #[test]
-    fn test_not_expression_tag1() {
-        let suffix = "!";
-        Expression::variants().iter().for_each(|expr| {
-            let tag_on = format!("{}{}", expr.to_str(), suffix);
-            assert!(
-                not_expression_tag(tag_on.as_str()).is_err(),
-                "Passed on {}",
-                tag_on
-            );
-        });
+/// A cast such as `(uint)x` or `(ItemType)raw`. A parenthesized name that is
+/// not a built-in type is only a cast when an operand follows it, so
+/// `(size) - 1` stays a subtraction.
+fn cast(input: &str) -> ParseResult<'_, Expr> {
+    let (rest, ty) = delimited(ws(char('(')), type_specifier, char(')'))(input)?;
+    if !is_typedef_member(&ty.name) && !starts_operand(rest) {
+        return Err(nom::Err::Error(nom::error_position!(
+            input,
+            nom::error::ErrorKind::Verify
+        )));
     }
+    let (rest, expr) = unary(rest)?;
+    Ok((
+        rest,
+        Expr::Cast {
+            ty,
+            expr: Box::new(expr),
+        },
+    ))
+}
 
-    #[test]
-    fn test_not_expression_tag2() {
-        let suffix = "a";
-        let tag_on = format!("{}", suffix);
-        assert!(
-            not_expression_tag(tag_on.as_str()).is_ok(),
-            "Failed on {}",
-            tag_on
-        );
-    }
+fn unary(input: &str) -> ParseResult<'_, Expr> {
+    alt((
+        map(pair(prefix_operator, unary), |(op, operand)| {
+            Expr::UnaryOp {
+                op,
+                operand: Box::new(operand),
+            }
+        }),
+        cast,
+        postfix,
+    ))(input)
+}
 
-    #[test]
-    fn test_not_expression_tag3() {
-        let tag_on = format!("!=");
-        assert!(
-            not_expression_tag(tag_on.as_str()).is_err(),
-            "Passed on {}",
-            tag_on
-        );
+/// The longest binary or assignment operator at the start of `input`. A
+/// prefix operator may follow directly, so `a&&!b` splits into `&&` and
+/// `!b`, and `x=-1` into `=` and `-1`.
+fn binary_operator(input: &str) -> ParseResult<'_, Expression> {
+    let (input, _) = multispace_or_comment0(input)?;
+    let longest = Expression::variants()
+        .iter()
+        .filter(|op| op.precedence().is_some() || op.is_assignment())
+        .filter(|op| input.starts_with(op.to_str()))
+        .max_by_key(|op| op.to_str().len());
+    match longest {
+        Some(op) => Ok((&input[op.to_str().len()..], op.clone())),
+        None => Err(nom::Err::Error(nom::error_position!(
+            input,
+            nom::error::ErrorKind::Tag
+        ))),
     }
 }
 
-fn hex_number(input: &str) -> IResult<&str, &str> {
-    tuple((
-        space0,
-        recognize(tuple((tag("0x"), many1(satisfy(|c| c.is_hex_digit()))))),
-        opt(take_while1(|c: char| c.is_whitespace())),
-    ))(input)
-    .map(|(i, o)| {
-        return (i.trim(), o.1.trim());
-    })
+/// Precedence climbing: parses operands joined by binary operators that
+/// bind at least as tightly as `min_precedence`, folding them to the left.
+fn binary_expression(input: &str, min_precedence: u8) -> ParseResult<'_, Expr> {
+    let (mut input, mut left) = unary(input)?;
+    while let Ok((after_operator, op)) = binary_operator(input) {
+        let precedence = match op.precedence() {
+            Some(precedence) if precedence >= min_precedence => precedence,
+            _ => break,
+        };
+        let (rest, right) = binary_expression(after_operator, precedence + 1)?;
+        left = Expr::BinaryOp {
+            left: Box::new(left),
+            op,
+            right: Box::new(right),
+        };
+        input = rest;
+    }
+    Ok((input, left))
 }
 
-fn decimal_number(input: &str) -> IResult<&str, &str> {
-    tuple((
-        space0,
-        take_while1(|c: char| c.is_digit(10)),
-        peek(not(many1(satisfy(|c| {
-            c.is_alphabetic() || c.is_ascii_punctuation()
-        })))),
-    ))(input)
-    .map(|(i, o)| (i.trim(), o.1.trim()))
-}
-fn hex_number_with_paren(input: &str) -> IResult<&str, &str> {
-    tuple((
-        space0,
-        recognize(tuple((tag("0x"), many1(satisfy(|c| c.is_hex_digit()))))),
-        opt(take_while1(|c: char| c.is_whitespace() || c != ')')),
-    ))(input)
-    .map(|(i, o)| {
-        return (i.trim(), o.1.trim());
-    })
+fn ternary(input: &str) -> ParseResult<'_, Expr> {
+    let (input, condition) = binary_expression(input, 0)?;
+    let Ok((input, _)) = ws(char('?'))(input) else {
+        return Ok((input, condition));
+    };
+    let (input, then_branch) = expression(input)?;
+    let (input, _) = ws(char(':'))(input)?;
+    let (input, else_branch) = ternary(input)?;
+    Ok((
+        input,
+        Expr::Ternary {
+            condition: Box::new(condition),
+            then_branch: Box::new(then_branch),
+            else_branch: Box::new(else_branch),
+        },
+    ))
 }
 
-fn decimal_number_with_paren(input: &str) -> IResult<&str, &str> {
-    tuple((
-        space0,
-        take_while1(|c: char| c.is_digit(10)),
-        peek(not(many1(satisfy(|c| {
-            (c.is_alphabetic() || c.is_ascii_punctuation()) && c != ')'
-        })))),
-    ))(input)
-    .map(|(i, o)| (i.trim(), o.1.trim()))
+fn assignment(input: &str) -> ParseResult<'_, Expr> {
+    let (input, _) = multispace_or_comment0(input)?;
+    let (rest, target) = ternary(input)?;
+    let Ok((rest, op)) = verify(binary_operator, Expression::is_assignment)(rest) else {
+        return Ok((rest, target));
+    };
+    if !target.is_lvalue() {
+        return failure(
+            input,
+            "assignment target must be an identifier, member access or index",
+        );
+    }
+    let (rest, value) = assignment(rest)?;
+    Ok((
+        rest,
+        Expr::Assign {
+            target: Box::new(target),
+            op,
+            value: Box::new(value),
+        },
+    ))
 }
 
-fn number(input: &str) -> IResult<&str, &str> {
-    ws(alt((hex_number, decimal_number)))(input).map(|(i, o)| (i.trim(), o.trim()))
+fn comma(input: &str) -> ParseResult<'_, Expr> {
+    let (input, first) = assignment(input)?;
+    let (input, mut others) = many0(preceded(ws(char(',')), assignment))(input)?;
+    if others.is_empty() {
+        return Ok((input, first));
+    }
+    others.insert(0, first);
+    Ok((input, Expr::Comma(others)))
 }
 
-fn number_with_paren(input: &str) -> IResult<&str, &str> {
-    ws(alt((
-        alt((hex_number, decimal_number)),
-        alt((hex_number_with_paren, decimal_number_with_paren)),
-    )))(input)
-    .map(|(i, o)| (i.trim(), o.trim()))
+/// Parses a C expression into an [`Expr`] tree, honouring C operator
+/// precedence and associativity: binary operators fold to the left, prefix
+/// operators, `?:` and assignments nest to the right, and the comma operator
+/// binds loosest of all.
+///
+/// # Example
+///
+/// ```
+/// use bt_parser::ast::{Expr, Expression, Literal};
+/// use bt_parser::parse_nested::expression;
+///
+/// let (rest, result) = expression("a + 2 * 3").unwrap();
+/// assert_eq!(rest, "");
+/// assert_eq!(
+///     result,
+///     Expr::BinaryOp {
+///         left: Box::new(Expr::Identifier("a".into())),
+///         op: Expression::Add,
+///         right: Box::new(Expr::BinaryOp {
+///             left: Box::new(Expr::Literal(Literal::Int(2))),
+///             op: Expression::Multiply,
+///             right: Box::new(Expr::Literal(Literal::Int(3))),
+///         }),
+///     }
+/// );
+/// ```
+pub fn expression(input: &str) -> ParseResult<'_, Expr> {
+    terminated(comma, multispace_or_comment0)(input)
 }
 
-fn expression_a(input: &str) -> IResult<&str, Vec<&str>> {
-    map(tuple((identifier, operator, number_with_paren)), |t| {
-        vec![t.0, t.1, t.2]
-    })(input)
-    .map(|(i, o)| (i.trim(), o.into_iter().map(|f| f.trim()).collect()))
+/// Parses a parenthesized, comma-separated argument list such as
+/// `(0x780, size * 2)`, as passed to a function or a parameterized struct.
+pub fn argument_list(input: &str) -> ParseResult<'_, Vec<Expr>> {
+    delimited(
+        char('('),
+        separated_list0(ws(char(',')), assignment),
+        ws(char(')')),
+    )(input)
 }
 
-fn expression_b(input: &str) -> IResult<&str, Vec<&str>> {
-    map(delimited(char('('), expression_a, char(')')), |v| v)(input)
-        .map(|(i, o)| (i.trim(), o.into_iter().map(|f| f.trim()).collect()))
+/// Parses a single assignment expression, stopping at a top-level `,`, as
+/// used for variable initializers such as `local int a = 1, b = a;`.
+pub fn assignment_expression(input: &str) -> ParseResult<'_, Expr> {
+    assignment(input)
 }
 
-pub fn expression(input: &str) -> IResult<&str, Vec<&str>> {
-    alt((expression_a, expression_b))(input)
+/// Parses a C constant expression: a conditional expression without
+/// assignments or top-level commas, as used for enum values and case
+/// labels, where a `,` or `:` ends the expression.
+///
+/// Unlike [`expression`], a comment trailing the expression is left in the
+/// remaining input so it can be attached as documentation.
+pub fn constant_expression(input: &str) -> ParseResult<'_, Expr> {
+    preceded(multispace_or_comment0, ternary)(input)
 }
 
-fn logical_and(input: &str) -> IResult<&str, Vec<Vec<&str>>> {
-    separated_list1(ws(tag("&&")), expression)(input)
+/// Parses a C shift expression: arithmetic and shifts, without the
+/// comparison, bitwise and logical operators, as used for bitfield widths.
+/// A `<` therefore ends the expression, so in `uint32 a : 1 << 2 <format=hex>;`
+/// the width stops where the attribute list starts.
+pub fn shift_expression(input: &str) -> ParseResult<'_, Expr> {
+    let precedence = Expression::BinaryShiftLeft.precedence().unwrap_or_default();
+    let (input, _) = multispace_or_comment0(input)?;
+    binary_expression(input, precedence)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ast::Literal;
     use pretty_assertions::assert_eq;
 
+    fn ident(name: &str) -> Box<Expr> {
+        Box::new(Expr::Identifier(name.into()))
+    }
+
+    fn literal(value: i64) -> Box<Expr> {
+        Box::new(Expr::Literal(Literal::Int(value)))
+    }
+
+    fn binary(left: Box<Expr>, op: Expression, right: Box<Expr>) -> Box<Expr> {
+        Box::new(Expr::BinaryOp { left, op, right })
+    }
+
+    fn parens(inner: Box<Expr>) -> Box<Expr> {
+        Box::new(Expr::Parens(inner))
+    }
+
     #[test]
     fn test_parse_conditional1() {
         let input = r#"(ItemID != 0) && ((ItemID & 0xf0000000) == 0)"#;
-        let expected_rest = r#"&& ((ItemID & 0xf0000000) == 0)"#;
-        let expected_result = vec!["ItemID","!=","0"];
+        let expected_rest = r#""#;
+        let expected_result = binary(
+            parens(binary(ident("ItemID"), Expression::NotEquals, literal(0))),
+            Expression::And,
+            parens(binary(
+                parens(binary(
+                    ident("ItemID"),
+                    Expression::BinaryAnd,
+                    literal(0xf0000000),
+                )),
+                Expression::Equals,
+                literal(0),
+            )),
+        );
 
         let (rest, result) = expression(input).unwrap();
 
         assert_eq!(rest, expected_rest);
-        assert_eq!(result, expected_result);
+        assert_eq!(Box::new(result), expected_result);
     }
 
     #[test]
     fn test_parse_conditional2() {
         let input = r#"ItemID != 0"#;
         let expected_rest = r#""#;
-        let expected_result = vec!["ItemID", "!=", "0"];
+        let expected_result = binary(ident("ItemID"), Expression::NotEquals, literal(0));
 
         let (rest, result) = expression(input).unwrap();
 
         assert_eq!(rest, expected_rest);
-        assert_eq!(result, expected_result);
+        assert_eq!(Box::new(result), expected_result);
+    }
+
+    #[test]
+    fn test_precedence1() {
+        let (rest, result) = expression("a || b && c | d ^ e & f == g < h << i + j * k").unwrap();
+        assert_eq!(rest, "");
+        let expected = binary(
+            ident("a"),
+            Expression::Or,
+            binary(
+                ident("b"),
+                Expression::And,
+                binary(
+                    ident("c"),
+                    Expression::BinaryOr,
+                    binary(
+                        ident("d"),
+                        Expression::BinaryXor,
+                        binary(
+                            ident("e"),
+                            Expression::BinaryAnd,
+                            binary(
+                                ident("f"),
+                                Expression::Equals,
+                                binary(
+                                    ident("g"),
+                                    Expression::LessThan,
+                                    binary(
+                                        ident("h"),
+                                        Expression::BinaryShiftLeft,
+                                        binary(
+                                            ident("i"),
+                                            Expression::Add,
+                                            binary(ident("j"), Expression::Multiply, ident("k")),
+                                        ),
+                                    ),
+                                ),
+                            ),
+                        ),
+                    ),
+                ),
+            ),
+        );
+        assert_eq!(Box::new(result), expected);
+    }
+
+    #[test]
+    fn test_precedence2() {
+        let (rest, result) = expression("a * b + c").unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(
+            Box::new(result),
+            binary(
+                binary(ident("a"), Expression::Multiply, ident("b")),
+                Expression::Add,
+                ident("c")
+            )
+        );
     }
 
+    #[test]
+    fn test_left_associativity() {
+        let (rest, result) = expression("a - b - c % 4 / 2").unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(
+            Box::new(result),
+            binary(
+                binary(ident("a"), Expression::Subtract, ident("b")),
+                Expression::Subtract,
+                binary(
+                    binary(ident("c"), Expression::Modulus, literal(4)),
+                    Expression::Divide,
+                    literal(2)
+                ),
+            )
+        );
+    }
+
+    #[test]
+    fn test_function_call() {
+        let (rest, result) = expression("ReadUInt(FTell() + 4, 0x10) >= size_2").unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(
+            Box::new(result),
+            binary(
+                Box::new(Expr::FunctionCall {
+                    name: "ReadUInt".into(),
+                    args: vec![
+                        *binary(
+                            Box::new(Expr::FunctionCall {
+                                name: "FTell".into(),
+                                args: vec![],
+                            }),
+                            Expression::Add,
+                            literal(4),
+                        ),
+                        Expr::Literal(Literal::Int(16)),
+                    ],
+                }),
+                Expression::GreaterThanOrEqualTo,
+                ident("size_2"),
+            )
+        );
+    }
+
+    #[test]
+    fn test_nested_parens() {
+        let (rest, result) = expression("((( a )))\n{").unwrap();
+        assert_eq!(rest, "{");
+        assert_eq!(Box::new(result), parens(parens(parens(ident("a")))));
+    }
+
+    #[test]
+    fn test_multiline() {
+        let (rest, result) = expression("(a\n    && b)").unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(
+            Box::new(result),
+            parens(binary(ident("a"), Expression::And, ident("b")))
+        );
+    }
+
+    #[test]
+    fn test_stops_at_non_binary_operator() {
+        let (rest, result) = expression("a ;").unwrap();
+        assert_eq!(rest, ";");
+        assert_eq!(Box::new(result), ident("a"));
+    }
+
+    #[test]
+    fn test_binary_operator() {
+        for op in Expression::variants() {
+            if op.precedence().is_some() || op.is_assignment() {
+                assert_eq!(binary_operator(op.to_str()), Ok(("", op.clone())));
+            }
+        }
+        assert_eq!(
+            binary_operator(" <<= 1"),
+            Ok((" 1", Expression::BinaryShiftLeftEquals))
+        );
+        assert_eq!(binary_operator("&&!b"), Ok(("!b", Expression::And)));
+        assert!(binary_operator("!b").is_err());
+        assert!(binary_operator("?:").is_err());
+    }
+
+    #[test]
+    fn test_unspaced_prefix_operands() {
+        let (rest, result) = expression("a&&!b").unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(
+            Box::new(result),
+            binary(
+                ident("a"),
+                Expression::And,
+                unary(Expression::Not, ident("b"))
+            )
+        );
+        let minus_one = || {
+            unary(
+                Expression::Subtract,
+                Box::new(Expr::Literal(Literal::Int(1))),
+            )
+        };
+        for (input, op) in [
+            ("a==-1", Expression::Equals),
+            ("x*-1", Expression::Multiply),
+            ("a<-1", Expression::LessThan),
+        ] {
+            let (rest, result) = expression(input).unwrap();
+            assert_eq!(rest, "", "{}", input);
+            assert_eq!(
+                Box::new(result),
+                binary(ident(input.get(..1).unwrap()), op, minus_one())
+            );
+        }
+        let (rest, result) = expression("x=-1").unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(
+            result,
+            Expr::Assign {
+                target: ident("x"),
+                op: Expression::Assign,
+                value: minus_one(),
+            }
+        );
+        let (rest, result) = expression("i=~m").unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(
+            result,
+            Expr::Assign {
+                target: ident("i"),
+                op: Expression::Assign,
+                value: unary(Expression::BinaryInvert, ident("m")),
+            }
+        );
+        let (rest, result) = expression("a--b").unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(
+            Box::new(result),
+            binary(
+                ident("a"),
+                Expression::Subtract,
+                unary(Expression::Subtract, ident("b"))
+            )
+        );
+    }
+
+    #[test]
+    fn test_number_errors() {
+        assert!(expression("99999999999999999999").is_err());
+        assert!(expression("0x1G").is_err());
+        assert!(expression("12ab").is_err());
+    }
+
+    #[test]
+    fn test_typed_literals() {
+        let (rest, result) = expression(r#"Printf("%s\n", 'RIFF', 1.5, 0x10u)"#).unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(
+            result,
+            Expr::FunctionCall {
+                name: "Printf".into(),
+                args: vec![
+                    Expr::Literal(Literal::String("%s\n".into())),
+                    Expr::Literal(Literal::Char(0x52494646)),
+                    Expr::Literal(Literal::Float(1.5)),
+                    Expr::Literal(Literal::UInt(16)),
+                ],
+            }
+        );
+    }
+
+    fn unary(op: Expression, operand: Box<Expr>) -> Box<Expr> {
+        Box::new(Expr::UnaryOp { op, operand })
+    }
+
+    fn index(object: Box<Expr>, index: Box<Expr>) -> Box<Expr> {
+        Box::new(Expr::Index { object, index })
+    }
+
+    fn ternary(condition: Box<Expr>, then_branch: Box<Expr>, else_branch: Box<Expr>) -> Box<Expr> {
+        Box::new(Expr::Ternary {
+            condition,
+            then_branch,
+            else_branch,
+        })
+    }
+
+    #[test]
+    fn test_prefix_operators() {
+        let (rest, result) = expression("!a && ~b == -c + +d").unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(
+            Box::new(result),
+            binary(
+                unary(Expression::Not, ident("a")),
+                Expression::And,
+                binary(
+                    unary(Expression::BinaryInvert, ident("b")),
+                    Expression::Equals,
+                    binary(
+                        unary(Expression::Subtract, ident("c")),
+                        Expression::Add,
+                        unary(Expression::Add, ident("d")),
+                    ),
+                ),
+            )
+        );
+    }
+
+    #[test]
+    fn test_nested_prefix_operators() {
+        let (rest, result) = expression("!!-a").unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(
+            Box::new(result),
+            unary(
+                Expression::Not,
+                unary(Expression::Not, unary(Expression::Subtract, ident("a")))
+            )
+        );
+    }
+
+    #[test]
+    fn test_increment_decrement() {
+        let (rest, result) = expression("++i + j--").unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(
+            Box::new(result),
+            binary(
+                unary(Expression::Increment, ident("i")),
+                Expression::Add,
+                Box::new(Expr::PostfixOp {
+                    op: Expression::Decrement,
+                    operand: ident("j"),
+                }),
+            )
+        );
+    }
+
+    #[test]
+    fn test_index_and_member() {
+        let (rest, result) = expression("header.entries[i + 1].count * this[0]").unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(
+            Box::new(result),
+            binary(
+                Box::new(Expr::Member {
+                    object: index(
+                        Box::new(Expr::Member {
+                            object: ident("header"),
+                            member: "entries".into(),
+                        }),
+                        binary(ident("i"), Expression::Add, literal(1)),
+                    ),
+                    member: "count".into(),
+                }),
+                Expression::Multiply,
+                index(ident("this"), literal(0)),
+            )
+        );
+    }
+
+    #[test]
+    fn test_cast() {
+        let (rest, result) = expression("(uint)x[1] >> 4").unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(
+            Box::new(result),
+            binary(
+                Box::new(Expr::Cast {
+                    ty: "uint".into(),
+                    expr: index(ident("x"), literal(1)),
+                }),
+                Expression::BinaryShiftRight,
+                literal(4),
+            )
+        );
+
+        let (_, result) = expression("(unsigned char)c").unwrap();
+        assert_eq!(
+            result,
+            Expr::Cast {
+                ty: "uchar".into(),
+                expr: ident("c"),
+            }
+        );
+    }
+
+    #[test]
+    fn test_cast_to_user_type() {
+        let (rest, result) = expression("x = (MyType)y").unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(
+            result,
+            Expr::Assign {
+                target: ident("x"),
+                op: Expression::Assign,
+                value: Box::new(Expr::Cast {
+                    ty: "MyType".into(),
+                    expr: ident("y"),
+                }),
+            }
+        );
+        let (_, result) = expression("(Flags)~mask").unwrap();
+        assert!(matches!(result, Expr::Cast { ty, .. } if ty == "Flags".into()));
+    }
+
+    #[test]
+    fn test_parens_are_not_casts() {
+        let (rest, result) = expression("(size) - 1").unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(
+            Box::new(result),
+            binary(parens(ident("size")), Expression::Subtract, literal(1))
+        );
+    }
+
+    #[test]
+    fn test_ternary1() {
+        let (rest, result) = expression("(this < 0 ? cRed : cNone )").unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(
+            Box::new(result),
+            parens(ternary(
+                binary(ident("this"), Expression::LessThan, literal(0)),
+                ident("cRed"),
+                ident("cNone"),
+            ))
+        );
+    }
+
+    #[test]
+    fn test_ternary2() {
+        let (rest, result) = expression("a ? b ? c : d : e ? f : g").unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(
+            Box::new(result),
+            ternary(
+                ident("a"),
+                ternary(ident("b"), ident("c"), ident("d")),
+                ternary(ident("e"), ident("f"), ident("g")),
+            )
+        );
+    }
+
+    #[test]
+    fn test_ternary3() {
+        assert!(expression("a ? b").is_err());
+        assert!(expression("a ? b :").is_err());
+    }
+
+    fn assign(target: Box<Expr>, op: Expression, value: Box<Expr>) -> Box<Expr> {
+        Box::new(Expr::Assign { target, op, value })
+    }
+
+    #[test]
+    fn test_assignment1() {
+        let (rest, result) = expression("x = ReadUInt(FTell())").unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(
+            Box::new(result),
+            assign(
+                ident("x"),
+                Expression::Assign,
+                Box::new(Expr::FunctionCall {
+                    name: "ReadUInt".into(),
+                    args: vec![Expr::FunctionCall {
+                        name: "FTell".into(),
+                        args: vec![],
+                    }],
+                }),
+            )
+        );
+    }
+
+    #[test]
+    fn test_assignment_is_right_associative() {
+        let (rest, result) = expression("a = b += c ? 1 : 2").unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(
+            Box::new(result),
+            assign(
+                ident("a"),
+                Expression::Assign,
+                assign(
+                    ident("b"),
+                    Expression::AddEquals,
+                    ternary(ident("c"), literal(1), literal(2)),
+                ),
+            )
+        );
+    }
+
+    #[test]
+    fn test_compound_assignments() {
+        let compound = [
+            Expression::AddEquals,
+            Expression::MinusEquals,
+            Expression::MultiplyEquals,
+            Expression::DivideEquals,
+            Expression::BinaryAndEquals,
+            Expression::BinaryXorEquals,
+            Expression::BinaryModulusEquals,
+            Expression::BinaryOrEquals,
+            Expression::BinaryShiftLeftEquals,
+            Expression::BinaryShiftRightEquals,
+        ];
+        for op in compound {
+            let input = format!("entries[i].count {} 2", op.to_str());
+            let (rest, result) = expression(input.as_str()).unwrap();
+            assert_eq!(rest, "", "Failed on {}", input);
+            assert_eq!(
+                Box::new(result),
+                assign(
+                    Box::new(Expr::Member {
+                        object: index(ident("entries"), ident("i")),
+                        member: "count".into(),
+                    }),
+                    op.clone(),
+                    literal(2),
+                ),
+                "Failed on {}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn test_comma() {
+        let (rest, result) = expression("i = 0, j = f(a, b), (k, l)").unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(
+            result,
+            Expr::Comma(vec![
+                *assign(ident("i"), Expression::Assign, literal(0)),
+                *assign(
+                    ident("j"),
+                    Expression::Assign,
+                    Box::new(Expr::FunctionCall {
+                        name: "f".into(),
+                        args: vec![*ident("a"), *ident("b")],
+                    }),
+                ),
+                *parens(Box::new(Expr::Comma(vec![*ident("k"), *ident("l")]))),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_equals_is_not_assignment() {
+        let (rest, result) = expression("a == b").unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(
+            Box::new(result),
+            binary(ident("a"), Expression::Equals, ident("b"))
+        );
+    }
+
+    #[test]
+    fn test_invalid_assignment_target() {
+        for input in ["1 = 2", "a + b = c", "f(x) += 1", "a ? b : c = d", "-a = b"] {
+            match expression(input) {
+                Err(nom::Err::Failure(error)) => assert_eq!(
+                    error.errors[0].1,
+                    nom::error::VerboseErrorKind::Context(
+                        "assignment target must be an identifier, member access or index"
+                    ),
+                    "Failed on {}",
+                    input
+                ),
+                result => panic!("Expected failure on {}: {:?}", input, result),
+            }
+        }
+    }
+
+    #[test]
+    fn test_missing_operand() {
+        assert!(expression("a +").is_err());
+        assert!(expression("(a").is_err());
+    }
 }
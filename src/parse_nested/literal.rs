@@ -0,0 +1,319 @@
+use nom::{
+    branch::alt,
+    bytes::complete::{tag_no_case, take_while, take_while1},
+    character::complete::{char, digit0, digit1, one_of, satisfy},
+    combinator::{map, not, opt, peek, recognize},
+    sequence::{pair, preceded, terminated, tuple},
+};
+
+use crate::{
+    ast::Literal,
+    error::{failure, ParseResult},
+};
+
+fn not_identifier_char(input: &str) -> ParseResult<'_, ()> {
+    peek(not(satisfy(|c: char| c.is_alphanumeric() || c == '_')))(input)
+}
+
+/// Splits an integer literal into its radix and digits, without the prefix.
+fn integer_digits(input: &str) -> ParseResult<'_, (u32, &str)> {
+    alt((
+        map(
+            preceded(
+                tag_no_case("0x"),
+                take_while1(|c: char| c.is_ascii_hexdigit()),
+            ),
+            |digits| (16, digits),
+        ),
+        map(
+            preceded(
+                tag_no_case("0b"),
+                take_while1(|c: char| c == '0' || c == '1'),
+            ),
+            |digits| (2, digits),
+        ),
+        // A leading zero followed by more digits is octal; `0` on its own is
+        // just decimal zero.
+        map(
+            preceded(char('0'), take_while1(|c: char| c.is_ascii_digit())),
+            |digits| (8, digits),
+        ),
+        map(digit1, |digits| (10, digits)),
+    ))(input)
+}
+
+/// Any combination of one `u` and up to two `l`, in either order.
+/// Returns whether the literal is unsigned.
+fn integer_suffix(input: &str) -> ParseResult<'_, bool> {
+    let (rest, suffix) = take_while(|c: char| matches!(c, 'u' | 'U' | 'l' | 'L'))(input)?;
+    let unsigned = suffix
+        .chars()
+        .filter(|c| c.eq_ignore_ascii_case(&'u'))
+        .count();
+    let long = suffix.len() - unsigned;
+    if unsigned > 1 || long > 2 {
+        return failure(input, "invalid integer suffix");
+    }
+    Ok((rest, unsigned == 1))
+}
+
+/// Parses a hex, binary, octal or decimal integer with an optional `u`/`L`
+/// suffix.
+///
+/// Values that fit in an `i64` are [`Literal::Int`] unless the `u` suffix is
+/// present; larger ones such as `0xffffffffffffffff` become
+/// [`Literal::UInt`]. Anything beyond 64 bits is a hard error.
+fn integer(input: &str) -> ParseResult<'_, Literal> {
+    let (rest, ((radix, digits), unsigned)) =
+        terminated(pair(integer_digits, integer_suffix), not_identifier_char)(input)?;
+
+    if radix == 8 && digits.contains(['8', '9']) {
+        return failure(input, "invalid digit in octal literal");
+    }
+    let Ok(value) = u64::from_str_radix(digits, radix) else {
+        return failure(input, "integer literal does not fit in 64 bits");
+    };
+    let literal = match i64::try_from(value) {
+        Ok(value) if !unsigned => Literal::Int(value),
+        _ => Literal::UInt(value),
+    };
+    Ok((rest, literal))
+}
+
+fn exponent(input: &str) -> ParseResult<'_, &str> {
+    recognize(tuple((one_of("eE"), opt(one_of("+-")), digit1)))(input)
+}
+
+/// Parses `1.5`, `.5`, `1.`, `1e10` or `2.5e-3`, with an optional `f`/`L`
+/// suffix.
+fn float(input: &str) -> ParseResult<'_, Literal> {
+    let (rest, text) = terminated(
+        recognize(alt((
+            recognize(tuple((digit1, char('.'), digit0, opt(exponent)))),
+            recognize(tuple((char('.'), digit1, opt(exponent)))),
+            recognize(pair(digit1, exponent)),
+        ))),
+        pair(opt(one_of("fFlL")), not_identifier_char),
+    )(input)?;
+
+    let Ok(value) = text.parse() else {
+        return failure(input, "invalid floating point literal");
+    };
+    Ok((rest, Literal::Float(value)))
+}
+
+/// A single unit of a quoted literal: either a source character or the
+/// numeric value of an escape sequence.
+enum Unit {
+    Char(char),
+    Escape(u32),
+}
+
+/// Decodes the escape sequence after a backslash.
+fn escape(input: &str) -> ParseResult<'_, u32> {
+    let Some(c) = input.chars().next() else {
+        return failure(input, "unterminated escape sequence");
+    };
+    let rest = &input[c.len_utf8()..];
+    let value = match c {
+        'n' => 0x0a,
+        't' => 0x09,
+        'r' => 0x0d,
+        'a' => 0x07,
+        'b' => 0x08,
+        'f' => 0x0c,
+        'v' => 0x0b,
+        '\\' | '\'' | '"' | '?' => c as u32,
+        '0'..='7' => {
+            let digits = input
+                .char_indices()
+                .take(3)
+                .take_while(|(_, c)| c.is_digit(8))
+                .last()
+                .map_or(0, |(i, c)| i + c.len_utf8());
+            let value = u32::from_str_radix(&input[..digits], 8).unwrap_or_default();
+            return Ok((&input[digits..], value));
+        }
+        'x' | 'u' | 'U' => {
+            let (after, digits) = take_while(|c: char| c.is_ascii_hexdigit())(rest)?;
+            let expected = match c {
+                'u' => Some(4),
+                'U' => Some(8),
+                _ => None,
+            };
+            if digits.is_empty() || expected.is_some_and(|len| digits.len() != len) {
+                return failure(input, "malformed hex escape sequence");
+            }
+            let Ok(value) = u32::from_str_radix(digits, 16) else {
+                return failure(input, "hex escape sequence out of range");
+            };
+            return Ok((after, value));
+        }
+        _ => return failure(input, "unknown escape sequence"),
+    };
+    Ok((rest, value))
+}
+
+/// Reads the body of a literal opened by `quote` up to and including the
+/// closing quote.
+fn quoted(quote: char) -> impl FnMut(&str) -> ParseResult<'_, Vec<Unit>> {
+    move |input| {
+        let (mut rest, _) = char(quote)(input)?;
+        let mut units = Vec::new();
+        loop {
+            match rest.chars().next() {
+                Some(c) if c == quote => return Ok((&rest[1..], units)),
+                Some('\\') => {
+                    let (after, value) = escape(&rest[1..])?;
+                    units.push(Unit::Escape(value));
+                    rest = after;
+                }
+                Some(c) if c != '\n' => {
+                    units.push(Unit::Char(c));
+                    rest = &rest[c.len_utf8()..];
+                }
+                _ => return failure(input, "unterminated quoted literal"),
+            }
+        }
+    }
+}
+
+/// Parses a `"..."` string, decoding escapes into their characters.
+fn string(input: &str) -> ParseResult<'_, String> {
+    let (rest, units) = quoted('"')(input)?;
+    let decoded = units
+        .into_iter()
+        .map(|unit| match unit {
+            Unit::Char(c) => Some(c),
+            Unit::Escape(value) => char::from_u32(value),
+        })
+        .collect::<Option<String>>();
+    match decoded {
+        Some(decoded) => Ok((rest, decoded)),
+        None => failure(input, "escape sequence is not a valid character"),
+    }
+}
+
+/// Parses a character constant. Multi-character constants such as `'RIFF'`
+/// pack their bytes big-endian, the way 010 Editor evaluates them.
+fn character(input: &str) -> ParseResult<'_, u64> {
+    let (rest, units) = quoted('\'')(input)?;
+    let mut bytes = Vec::new();
+    for unit in units {
+        match unit {
+            Unit::Char(c) => bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes()),
+            Unit::Escape(value) => match u8::try_from(value) {
+                Ok(byte) => bytes.push(byte),
+                Err(_) => return failure(input, "escape sequence out of range for a character"),
+            },
+        }
+    }
+    match bytes.len() {
+        0 => failure(input, "empty character constant"),
+        1..=8 => Ok((
+            rest,
+            bytes
+                .into_iter()
+                .fold(0, |value, byte| (value << 8) | u64::from(byte)),
+        )),
+        _ => failure(input, "character constant does not fit in 64 bits"),
+    }
+}
+
+/// Parses any literal: a number, a character constant, or a narrow or wide
+/// string.
+///
+/// Malformed literals, such as out-of-range integers or unknown escapes, are
+/// reported as `Err::Failure` so the error points at the literal itself.
+///
+/// # Example
+///
+/// ```
+/// use bt_parser::ast::Literal;
+/// use bt_parser::parse_nested::literal::literal;
+///
+/// assert_eq!(literal("0xf0000000"), Ok(("", Literal::Int(0xf0000000))));
+/// assert_eq!(literal("'RIFF'"), Ok(("", Literal::Char(0x52494646))));
+/// assert_eq!(literal(r#"L"a\tb""#), Ok(("", Literal::WideString("a\tb".into()))));
+/// ```
+pub fn literal(input: &str) -> ParseResult<'_, Literal> {
+    alt((
+        map(preceded(char('L'), string), Literal::WideString),
+        map(string, Literal::String),
+        map(character, Literal::Char),
+        float,
+        integer,
+    ))(input)
+}
+
+#[cfg(test)]
+mod literal_tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_literal_integers() {
+        assert_eq!(literal("42"), Ok(("", Literal::Int(42))));
+        assert_eq!(literal("0"), Ok(("", Literal::Int(0))));
+        assert_eq!(literal("0x1B0"), Ok(("", Literal::Int(0x1B0))));
+        assert_eq!(literal("017"), Ok(("", Literal::Int(0o17))));
+        assert_eq!(literal("0b1010"), Ok(("", Literal::Int(10))));
+        assert_eq!(literal("10u"), Ok(("", Literal::UInt(10))));
+        assert_eq!(literal("10L"), Ok(("", Literal::Int(10))));
+        assert_eq!(literal("0xffULL"), Ok(("", Literal::UInt(0xff))));
+        assert_eq!(
+            literal("0xffffffffffffffff"),
+            Ok(("", Literal::UInt(u64::MAX)))
+        );
+        assert_eq!(literal("12)"), Ok((")", Literal::Int(12))));
+    }
+
+    #[test]
+    fn test_literal_integer_errors() {
+        assert!(matches!(
+            literal("0x10000000000000000"),
+            Err(nom::Err::Failure(_))
+        ));
+        assert!(matches!(literal("09"), Err(nom::Err::Failure(_))));
+        assert!(matches!(literal("1uu"), Err(nom::Err::Failure(_))));
+        assert!(literal("12ab").is_err());
+    }
+
+    #[test]
+    fn test_literal_floats() {
+        assert_eq!(literal("1.5"), Ok(("", Literal::Float(1.5))));
+        assert_eq!(literal(".25f"), Ok(("", Literal::Float(0.25))));
+        assert_eq!(literal("2."), Ok(("", Literal::Float(2.0))));
+        assert_eq!(literal("1e3"), Ok(("", Literal::Float(1000.0))));
+        assert_eq!(literal("2.5E-1"), Ok(("", Literal::Float(0.25))));
+    }
+
+    #[test]
+    fn test_literal_strings() {
+        assert_eq!(
+            literal(r#""<%g %g>" rest"#),
+            Ok((" rest", Literal::String("<%g %g>".into())))
+        );
+        assert_eq!(
+            literal(r#""a\n\"b\"\x41\101\0""#),
+            Ok(("", Literal::String("a\n\"b\"AA\0".into())))
+        );
+        assert_eq!(
+            literal(r#"L"é""#),
+            Ok(("", Literal::WideString("é".into())))
+        );
+        assert!(matches!(literal(r#""abc"#), Err(nom::Err::Failure(_))));
+        assert!(matches!(literal(r#""\q""#), Err(nom::Err::Failure(_))));
+    }
+
+    #[test]
+    fn test_literal_characters() {
+        assert_eq!(literal("'A'"), Ok(("", Literal::Char(0x41))));
+        assert_eq!(literal(r"'\n'"), Ok(("", Literal::Char(0x0a))));
+        assert_eq!(literal(r"'\xff'"), Ok(("", Literal::Char(0xff))));
+        assert_eq!(literal("'RIFF'"), Ok(("", Literal::Char(0x52494646))));
+        assert!(matches!(literal("''"), Err(nom::Err::Failure(_))));
+        assert!(matches!(literal("'ABCDEFGHI'"), Err(nom::Err::Failure(_))));
+        assert!(matches!(literal(r"'\x100'"), Err(nom::Err::Failure(_))));
+    }
+}
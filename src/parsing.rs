@@ -1,6 +1,13 @@
 pub mod comment_line;
-pub mod typedef_line;
 pub mod conditional_line;
-pub mod parse_nested_parens;
 pub mod declaration_line;
+pub mod function_line;
+pub mod loop_line;
+pub mod parameter_list;
 pub mod parse_brackets;
+pub mod parse_nested_parens;
+pub mod statement;
+pub mod switch_line;
+pub mod type_specifier;
+pub mod typedef_line;
+pub mod variable_line;
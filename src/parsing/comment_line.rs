@@ -1,24 +1,93 @@
 use nom::{
-    bytes::complete::{tag, take_while},
-    character::complete::multispace0,
+    branch::alt,
+    bytes::complete::{tag, take_until, take_while},
+    character::complete::{multispace0, multispace1},
+    combinator::{map, recognize},
     error::context,
-    sequence::tuple,
-    IResult,
+    multi::many0,
+    sequence::{pair, tuple},
 };
 
-pub fn comment_line(input: &str) -> IResult<&str, (&str, &str)> {
+use crate::error::{failure, ParseResult};
+
+fn line_comment(input: &str) -> ParseResult<'_, (&str, &str)> {
+    pair(tag("//"), take_while(|c| c != '\n'))(input)
+}
+
+fn block_comment(input: &str) -> ParseResult<'_, (&str, &str)> {
+    let (rest, open) = tag("/*")(input)?;
+    match take_until::<_, _, ()>("*/")(rest) {
+        Ok((rest, text)) => Ok((&rest[2..], (open, text))),
+        Err(_) => failure(input, "unterminated block comment"),
+    }
+}
+
+/// Parses a `// line` or `/* block */` comment, returning the opening tag
+/// and the trimmed comment text.
+pub fn comment_line(input: &str) -> ParseResult<'_, (&str, &str)> {
     let mut parser = context(
         "comment line",
-        tuple((
-            tuple((multispace0, tag("//"))),
-            take_while(|c| c != '\n'),
-        )),
+        tuple((multispace0, alt((line_comment, block_comment)))),
     );
-    parser(input).map(|(next_input, result)| {
-        let (comment, text) = result;
-        let (_, tag) = comment;
-        (next_input, (tag, text.trim()))
-    })
+    parser(input).map(|(next_input, (_, (tag, text)))| (next_input, (tag, text.trim())))
+}
+
+/// Skips any mix of whitespace and comments, returning the skipped text.
+///
+/// Use this wherever a statement allows whitespace between its tokens, so a
+/// comment in the middle of a declaration does not end it.
+///
+/// # Example
+///
+/// ```
+/// use bt_parser::parsing::comment_line::multispace_or_comment0;
+///
+/// let result = multispace_or_comment0(" /* size */ // bytes\n uint32 size;");
+/// assert_eq!(result, Ok(("uint32 size;", " /* size */ // bytes\n ")));
+/// ```
+pub fn multispace_or_comment0(input: &str) -> ParseResult<'_, &str> {
+    recognize(many0(alt((
+        multispace1,
+        map(line_comment, |(tag, _)| tag),
+        map(block_comment, |(tag, _)| tag),
+    ))))(input)
+}
+
+/// A comment on the same line as the end of the previous item, returning
+/// the input after it and its text.
+pub fn trailing_comment(input: &str) -> Option<(&str, &str)> {
+    let after_spaces = input.trim_start_matches([' ', '\t']);
+    if !after_spaces.starts_with("//") && !after_spaces.starts_with("/*") {
+        return None;
+    }
+    comment_line(after_spaces)
+        .ok()
+        .map(|(rest, (_, text))| (rest, text))
+}
+
+/// Skips whitespace and comments before an item, returning the comments
+/// that document it: those directly above it with no blank line in between,
+/// joined by newlines.
+pub fn doc_comments(input: &str) -> ParseResult<'_, Option<String>> {
+    let mut docs: Vec<&str> = Vec::new();
+    let mut rest = input;
+    loop {
+        let trimmed = rest.trim_start();
+        if rest[..rest.len() - trimmed.len()].matches('\n').count() > 1 {
+            docs.clear();
+        }
+        match comment_line(trimmed) {
+            Ok((next, (_, text))) => {
+                docs.push(text);
+                rest = next;
+            }
+            Err(nom::Err::Failure(error)) => return Err(nom::Err::Failure(error)),
+            Err(_) => {
+                let doc = Some(docs.join("\n")).filter(|_| !docs.is_empty());
+                return Ok((trimmed, doc));
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -43,6 +112,46 @@ mod comment_line_tests {
     fn test_comment_line3() {
         let input = "//This is a comment\n typedef struct {\n";
         let result = comment_line(input);
-        assert_eq!(result, Ok(("\n typedef struct {\n", ("//", "This is a comment"))));
+        assert_eq!(
+            result,
+            Ok(("\n typedef struct {\n", ("//", "This is a comment")))
+        );
+    }
+
+    #[test]
+    fn test_comment_line4() {
+        let input = "/* Elden Ring\n   save file */\nint a;";
+        let result = comment_line(input);
+        assert_eq!(result, Ok(("\nint a;", ("/*", "Elden Ring\n   save file"))));
+    }
+
+    #[test]
+    fn test_comment_line5() {
+        let input = "/* never closed\nint a;";
+        assert!(matches!(comment_line(input), Err(nom::Err::Failure(_))));
+    }
+
+    #[test]
+    fn test_multispace_or_comment0() {
+        assert_eq!(multispace_or_comment0("int a;"), Ok(("int a;", "")));
+        assert_eq!(
+            multispace_or_comment0("/*a*//*b*/ a"),
+            Ok(("a", "/*a*//*b*/ "))
+        );
+    }
+
+    #[test]
+    fn test_trailing_comment() {
+        assert_eq!(trailing_comment(" // x\nint a;"), Some(("\nint a;", "x")));
+        assert_eq!(trailing_comment("\n// x\nint a;"), None);
+    }
+
+    #[test]
+    fn test_doc_comments() {
+        assert_eq!(
+            doc_comments("// old\n\n// first\n/* second */\n  A,"),
+            Ok(("A,", Some("first\nsecond".into())))
+        );
+        assert_eq!(doc_comments("  A,"), Ok(("A,", None)));
     }
 }
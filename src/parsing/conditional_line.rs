@@ -1,22 +1,37 @@
 use nom::{
     branch::alt,
-    bytes::complete::{tag, take_while1},
-    character::complete::{char, multispace0},
-    combinator::peek,
+    character::complete::char,
+    combinator::value,
     error::context,
-    sequence::{preceded, tuple},
-    IResult,
+    sequence::{delimited, pair, preceded, tuple},
 };
 
-use crate::types::nested::Nested;
+use crate::error::ParseResult;
 
-fn parse_conditional_line(input: &str) -> IResult<&str, (&str, char, &str)> {
+use crate::{
+    ast::{ConditionalHeader, ConditionalKeyword, ElseIf, Expr, IfStatement},
+    parse_nested::expression,
+    parsing::{
+        comment_line::multispace_or_comment0,
+        statement::{body, keyword},
+    },
+};
+
+fn parse_conditional_line(input: &str) -> ParseResult<'_, (ConditionalKeyword, Expr)> {
     let mut parser = context(
         "conditional_line",
         tuple((
-            alt((tag("if"), tag("else if"))),
-            peek(preceded(multispace0, char('('))),
-            take_while1(|c: char| c != '{'),
+            alt((
+                value(ConditionalKeyword::If, keyword("if")),
+                value(
+                    ConditionalKeyword::ElseIf,
+                    pair(keyword("else"), keyword("if")),
+                ),
+            )),
+            preceded(
+                multispace_or_comment0,
+                delimited(char('('), expression, char(')')),
+            ),
         )),
     );
     parser(input)
@@ -30,37 +45,123 @@ fn parse_conditional_line(input: &str) -> IResult<&str, (&str, char, &str)> {
 ///
 /// ```
 /// use nom::{IResult, error::ErrorKind};
+/// use bt_parser::ast::{ConditionalKeyword, Expr, Expression, ConditionalHeader, Literal};
 /// use bt_parser::parsing::conditional_line::conditional_line;
-/// use bt_parser::types::nested::Nested;
 ///
-/// let input = "if ( (ItemID != 0) && ((ItemID & 0xf0000000) == 0)) {";
+/// let input = "if (ItemID != 0) {";
 /// let result = conditional_line(input).unwrap();
-/// assert_eq!(result, ("{", Nested::List(vec!["if".into(),"( (ItemID != 0) && ((ItemID & 0xf0000000) == 0))".into(),].into())));
+/// assert_eq!(result, ("{", ConditionalHeader {
+///     keyword: ConditionalKeyword::If,
+///     condition: Expr::BinaryOp {
+///         left: Box::new(Expr::Identifier("ItemID".into())),
+///         op: Expression::NotEquals,
+///         right: Box::new(Expr::Literal(Literal::Int(0))),
+///     },
+/// }));
 /// ```
 ///
 /// # Parameters
 /// - `input`: A string slice that holds the input to be parsed.
 ///
 /// # Returns
-/// - `IResult<&str, Nested>`: On success, returns `Ok` wrapping the remaining input
-///   and a tuple containing the matched keyword (`"if"` or `"else if"`),
-///   and the condition string up to (but not including) the opening
-///   curly brace `{`. On failure, returns an error wrapped in `Err`.
-pub fn conditional_line(input: &str) -> IResult<&str, Nested> {
+/// - `ParseResult<'_, ConditionalHeader>`: On success, returns `Ok` wrapping the remaining input
+///   and a [`ConditionalHeader`] holding the matched keyword (`if` or `else if`)
+///   and the parsed condition between the outer parentheses. The opening
+///   curly brace `{` is left in the remaining input. On failure, returns an
+///   error wrapped in `Err`.
+pub fn conditional_line(input: &str) -> ParseResult<'_, ConditionalHeader> {
     parse_conditional_line(input).map(|(rest, result)| {
-        let (keyword, _, condition) = result;
-        (
-            rest.trim(),
-            vec![keyword.into(), condition.trim().into()].into(),
-        )
+        let (keyword, condition) = result;
+        (rest.trim_start(), ConditionalHeader { keyword, condition })
     })
 }
 
+/// Parses a whole `if` statement: the condition and body, then any number of
+/// `else if` branches and an optional `else`. Each body is a `{ ... }` block
+/// or a single statement.
+///
+/// # Example
+///
+/// ```
+/// use bt_parser::parsing::conditional_line::if_statement;
+///
+/// let input = "if (type == 0) { Weapon w; } else if (type == 1) Protector p; else int32 unk;";
+/// let (rest, result) = if_statement(input).unwrap();
+/// assert_eq!(rest, "");
+/// assert_eq!(result.body.len(), 1);
+/// assert_eq!(result.else_ifs.len(), 1);
+/// assert_eq!(result.else_body.map(|body| body.len()), Some(1));
+/// ```
+pub fn if_statement(input: &str) -> ParseResult<'_, IfStatement> {
+    let (rest, header) = conditional_line(input)?;
+    if header.keyword != ConditionalKeyword::If {
+        return Err(nom::Err::Error(nom::error_position!(
+            input,
+            nom::error::ErrorKind::Tag
+        )));
+    }
+    let (mut rest, body) = body(rest)?;
+    let mut else_ifs = Vec::new();
+    let mut else_body = None;
+    loop {
+        let else_if = conditional_line(rest)
+            .ok()
+            .filter(|(_, header)| header.keyword == ConditionalKeyword::ElseIf);
+        if let Some((after, header)) = else_if {
+            let (after, body) = self::body(after)?;
+            else_ifs.push(ElseIf {
+                condition: header.condition,
+                body,
+            });
+            rest = after;
+        } else if let Ok((after, _)) = keyword("else")(rest) {
+            let (after, body) = self::body(after)?;
+            else_body = Some(body);
+            rest = after;
+            break;
+        } else {
+            break;
+        }
+    }
+    Ok((
+        rest,
+        IfStatement {
+            condition: header.condition,
+            body,
+            else_ifs,
+            else_body,
+        },
+    ))
+}
+
 #[cfg(test)]
 mod conditional_line_tests {
     use super::*;
+    use crate::ast::{Expression, Literal, Statement};
     use pretty_assertions::assert_eq;
 
+    /// `(ItemID != 0) && ((ItemID & 0xf0000000) == mask)`
+    fn item_condition(mask: i64) -> Expr {
+        let item_id = || Box::new(Expr::Identifier("ItemID".into()));
+        Expr::BinaryOp {
+            left: Box::new(Expr::Parens(Box::new(Expr::BinaryOp {
+                left: item_id(),
+                op: Expression::NotEquals,
+                right: Box::new(Expr::Literal(Literal::Int(0))),
+            }))),
+            op: Expression::And,
+            right: Box::new(Expr::Parens(Box::new(Expr::BinaryOp {
+                left: Box::new(Expr::Parens(Box::new(Expr::BinaryOp {
+                    left: item_id(),
+                    op: Expression::BinaryAnd,
+                    right: Box::new(Expr::Literal(Literal::Int(0xf0000000))),
+                }))),
+                op: Expression::Equals,
+                right: Box::new(Expr::Literal(Literal::Int(mask))),
+            }))),
+        }
+    }
+
     #[test]
     fn test_conditional_line1() {
         let input_if_else = r#"if ( (ItemID != 0) && ((ItemID & 0xf0000000) == 0)) { 
@@ -73,13 +174,10 @@ mod conditional_line_tests {
           int32 unk;
           int32 unk2;
       }"#;
-        let expected_result = Nested::List(
-            vec![
-                "if".into(),
-                "( (ItemID != 0) && ((ItemID & 0xf0000000) == 0))".into(),
-            ]
-            .into(),
-        );
+        let expected_result = ConditionalHeader {
+            keyword: ConditionalKeyword::If,
+            condition: item_condition(0),
+        };
 
         let expected_rest = r#"{ 
           int32 unk; 
@@ -104,13 +202,10 @@ mod conditional_line_tests {
           int32 unk;
           int32 unk2;
       }"#;
-        let expected_result = Nested::List(
-            vec![
-                "else if".into(),
-                "((ItemID != 0) && ((ItemID & 0xf0000000) == 0x10000000))".into(),
-            ]
-            .into(),
-        );
+        let expected_result = ConditionalHeader {
+            keyword: ConditionalKeyword::ElseIf,
+            condition: item_condition(0x10000000),
+        };
         let expected_rest = r#"{
           int32 unk;
           int32 unk2;
@@ -120,4 +215,63 @@ mod conditional_line_tests {
         assert_eq!(rest, expected_rest);
         assert_eq!(result, expected_result);
     }
+
+    #[test]
+    fn test_if_statement() {
+        let input = r#"if (ItemID == 0) {
+    int32 unk;
+}
+// armor
+else if (ItemID == 1)
+    Protector protector;
+else if(ItemID == 2) { }
+else {
+    if (a) b++;
+    else c++;
+}
+int a;"#;
+        let (rest, result) = if_statement(input).unwrap();
+        assert_eq!(rest, "\nint a;");
+        assert_eq!(result.body.len(), 1);
+        let lengths: Vec<_> = result
+            .else_ifs
+            .iter()
+            .map(|branch| branch.body.len())
+            .collect();
+        assert_eq!(lengths, vec![1, 0]);
+        match result.else_body.as_deref() {
+            Some([Statement::If(nested)]) => {
+                assert_eq!(nested.else_ifs, vec![]);
+                assert_eq!(nested.else_body.as_ref().map(Vec::len), Some(1));
+            }
+            _ => panic!("Expected a nested if statement"),
+        }
+    }
+
+    #[test]
+    fn test_consecutive_if_statements() {
+        let (rest, result) = if_statement("if (a) { int x; } if (b) { int y; }").unwrap();
+        assert_eq!(rest, " if (b) { int y; }");
+        assert_eq!(result.else_ifs, vec![]);
+        assert_eq!(result.else_body, None);
+
+        let (rest, items) =
+            crate::parsing::statement::statement_list("if (a) { int x; }\nif (b) { int y; }")
+                .unwrap();
+        assert_eq!(rest, "");
+        assert!(matches!(&items[..], [Statement::If(_), Statement::If(_)]));
+    }
+
+    #[test]
+    fn test_if_statement_errors() {
+        assert!(if_statement("else if (a) { }").is_err());
+        assert!(if_statement("if (a) { int b; ").is_err());
+        assert!(if_statement("if (a) else { }").is_err());
+    }
+
+    #[test]
+    fn test_conditional_line3() {
+        assert!(conditional_line("if (ItemID != ) {").is_err());
+        assert!(conditional_line("if ItemID != 0 {").is_err());
+    }
 }
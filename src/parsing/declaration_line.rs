@@ -1,101 +1,217 @@
 pub mod special_attributes;
 
 use nom::{
-    bytes::complete::{tag, take_while1},
-    character::complete::{alphanumeric1, multispace0, multispace1},
-    combinator::{map, opt},
+    bytes::complete::tag,
+    character::complete::{char, one_of},
+    combinator::opt,
     error::context,
+    multi::many0,
     sequence::{delimited, preceded, tuple},
-    IResult,
 };
 
-use crate::types::nested::Nested;
+use crate::error::{failure, ParseResult};
 
-fn type_parser(input: &str) -> IResult<&str, &str> {
-    take_while1(|c: char| c.is_alphanumeric() || c == '_')(input)
+use crate::ast::{Declaration, Expr, TypeRef};
+use crate::parse_nested::{argument_list, expression, shift_expression};
+
+use self::special_attributes::attribute_list;
+use super::{
+    comment_line::multispace_or_comment0, statement::identifier, type_specifier::type_specifier,
+};
+
+/// One array bound such as `[size * 2]`, or `None` for an unsized `[]`.
+pub fn array_dimension(input: &str) -> ParseResult<'_, Option<Expr>> {
+    let (rest, _) = preceded(multispace_or_comment0, char('['))(input)?;
+    let (rest, (size, _)) = tuple((opt(expression), multispace_or_comment0))(rest)?;
+    match char::<_, ()>(']')(rest) {
+        Ok((rest, _)) => Ok((rest, size)),
+        Err(_) => failure(rest, "expected `]` after the array size"),
+    }
 }
 
-fn identifier_parser(input: &str) -> IResult<&str, &str> {
-    alphanumeric1(input)
+/// Whether `input` is where a declarator ends: at a `,`, the `;`, or an
+/// attribute list followed by either.
+fn ends_declarator(input: &str) -> bool {
+    tuple((
+        opt(preceded(multispace_or_comment0, attribute_list)),
+        multispace_or_comment0,
+        one_of(",;"),
+    ))(input)
+    .is_ok()
 }
 
-fn format_parser(input: &str) -> IResult<&str, &str> {
-    delimited(tag("<"), take_while1(|c: char| c != '>'), tag(">"))(input)
+/// The width after the `:` of a bitfield, e.g. `: FLAG_BITS - 1` or
+/// `: 1 << 2`. The width is a shift expression, so a `<` that opens the
+/// attributes is not read as a comparison; a comparison needs parentheses.
+fn parse_bitfield_width(input: &str) -> ParseResult<'_, Expr> {
+    let (rest, _) = preceded(multispace_or_comment0, char(':'))(input)?;
+    match shift_expression(rest) {
+        Ok((after, width)) if ends_declarator(after) => Ok((after, width)),
+        Ok(_) | Err(nom::Err::Error(_)) => failure(input, "expected a constant bitfield width"),
+        Err(error) => Err(error),
+    }
 }
 
-fn parse_declaration_content(input: &str) -> IResult<&str, (&str, &str, Option<&str>)> {
-    map(
-        tuple((
-            type_parser,
-            multispace1,
-            identifier_parser,
-            opt(preceded(multispace0, format_parser)),
-        )),
-        |(type_, _, identifier, special_attributes)| (type_, identifier, special_attributes),
-    )(input)
+/// One name declared with type `ty`, with its arguments, bounds, bitfield
+/// width and attributes, e.g. `inv(0x780) <format=hex>`.
+fn parse_declarator<'a>(ty: &TypeRef, input: &'a str) -> ParseResult<'a, Declaration> {
+    let (rest, (identifier, args, dimensions, bitfield, special_attributes)) = tuple((
+        opt(identifier),
+        opt(preceded(multispace_or_comment0, argument_list)),
+        many0(array_dimension),
+        opt(parse_bitfield_width),
+        opt(preceded(multispace_or_comment0, attribute_list)),
+    ))(input)?;
+    // Only a padding bitfield such as `uint32 : 4;` may leave out the name.
+    if identifier.is_none() && (bitfield.is_none() || args.is_some() || !dimensions.is_empty()) {
+        return Err(nom::Err::Error(nom::error_position!(
+            rest,
+            nom::error::ErrorKind::Alpha
+        )));
+    }
+    Ok((
+        rest,
+        Declaration {
+            ty: ty.clone(),
+            name: identifier.map(Into::into),
+            args: args.unwrap_or_default(),
+            dimensions,
+            bitfield,
+            attributes: special_attributes.unwrap_or_default(),
+            doc: None,
+        },
+    ))
 }
 
-fn parse_declaration_statement(
-    input: &str,
-) -> IResult<&str, (&str, &str, Option<&str>)> {
+fn parse_declaration_content(input: &str) -> ParseResult<'_, Declaration> {
+    let (rest, ty) = type_specifier(input)?;
+    parse_declarator(&ty, rest)
+}
+
+/// Parses the names declared after a type up to and including the `;`, as
+/// in `x, y[2] <format=hex>;`, giving each its own [`Declaration`] of type
+/// `ty`.
+pub fn declarator_list<'a>(ty: &TypeRef, input: &'a str) -> ParseResult<'a, Vec<Declaration>> {
+    let (mut rest, first) = parse_declarator(ty, input)?;
+    let mut declarations = vec![first];
+    while let Ok((after, _)) = preceded(multispace_or_comment0, char(','))(rest) {
+        let Ok((after, declaration)) = parse_declarator(ty, after) else {
+            return failure(after, "expected a name after `,`");
+        };
+        declarations.push(declaration);
+        rest = after;
+    }
+    let (rest, _) = preceded(multispace_or_comment0, tag(";"))(rest)?;
+    Ok((rest, declarations))
+}
+
+/// Parses a declaration of one or more names sharing a type, such as
+/// `uint16 x, y;`.
+///
+/// # Example
+///
+/// ```
+/// use bt_parser::parsing::declaration_line::declaration_list;
+///
+/// let (_, result) = declaration_list("uint16 x, y[2];").unwrap();
+/// assert_eq!(result[0].name.as_deref(), Some("x"));
+/// assert_eq!(result[1].ty, "uint16".into());
+/// assert_eq!(result[1].dimensions.len(), 1);
+/// ```
+pub fn declaration_list(input: &str) -> ParseResult<'_, Vec<Declaration>> {
+    let (rest, ty) = preceded(multispace_or_comment0, type_specifier)(input)?;
+    declarator_list(&ty, rest)
+}
+
+fn parse_declaration_statement(input: &str) -> ParseResult<'_, Declaration> {
     let mut parser = context(
         "declaration_statement",
-        delimited(multispace0, parse_declaration_content, tag(";")),
+        delimited(
+            multispace_or_comment0,
+            parse_declaration_content,
+            preceded(multispace_or_comment0, tag(";")),
+        ),
     );
     parser(input)
 }
 
-pub fn declaration_statement(
-    input: &str,
-) -> IResult<&str, Nested> {
-    parse_declaration_statement(input).map(|(rest, result)| {
-        let (type_, identifier, special_attributes) = result;
-        let format_as_nested: Nested = match special_attributes.map(|f| f.into()) {
-            Some(nested) => nested,
-            None => Nested::Text("".into()),
-        
-        };
-        (rest.trim(), Nested::List(vec![type_.into(), identifier.into(), format_as_nested]))
-    })
+pub fn declaration_statement(input: &str) -> ParseResult<'_, Declaration> {
+    parse_declaration_statement(input).map(|(rest, declaration)| (rest.trim_start(), declaration))
 }
 
 #[cfg(test)]
 mod declaration_statement_tests {
     use super::*;
+    use crate::ast::{Attribute, Expression, Literal};
+
+    fn declaration(ty: &str, name: &str) -> Declaration {
+        Declaration {
+            ty: ty.into(),
+            name: Some(name.into()),
+            args: vec![],
+            dimensions: vec![],
+            bitfield: None,
+            attributes: vec![],
+            doc: None,
+        }
+    }
 
     #[test]
     fn test_declaration_statement1() {
         let input = "int a;";
         let result = declaration_statement(input).unwrap();
-        assert_eq!(result, ("", vec!["int".into(), "a".into(), "".into()].into()));
+        assert_eq!(result, ("", declaration("int", "a")));
     }
 
     #[test]
     fn test_declaration_statement2() {
         let input = "int a; ";
         let result = declaration_statement(input).unwrap();
-        assert_eq!(result, ("", vec!["int".into(), "a".into(), "".into()].into()));
+        assert_eq!(result, ("", declaration("int", "a")));
     }
 
     #[test]
     fn test_declaration_statement3() {
         let input = " int a; ";
         let result = declaration_statement(input).unwrap();
-        assert_eq!(result, ("", vec!["int".into(), "a".into(), "".into()].into()));
+        assert_eq!(result, ("", declaration("int", "a")));
     }
 
     #[test]
     fn test_declaration_statement4() {
         let input = " int a;";
         let result = declaration_statement(input).unwrap();
-        assert_eq!(result, ("", vec!["int".into(), "a".into(), "".into()].into()));
+        assert_eq!(result, ("", declaration("int", "a")));
     }
 
     #[test]
-    fn test_declaration_statement_with_format() {
+    fn test_declaration_statement_rejects_bare_format() {
+        // 010 Editor attributes are `name=value` pairs, so a printf format
+        // belongs in a read function such as `<read=Str("0x%08X", this)>`.
         let input = "int a<0x%08X>;";
+        let result = declaration_statement(input);
+        assert!(matches!(result, Err(nom::Err::Error(_))));
+    }
+
+    #[test]
+    fn test_declaration_statement_with_attribute() {
+        let input = "int a<format=hex>;";
         let result = declaration_statement(input).unwrap();
-        assert_eq!(result, ("", vec!["int".into(), "a".into(), "0x%08X".into()].into()));
+        assert_eq!(
+            result,
+            (
+                "",
+                Declaration {
+                    attributes: vec![Attribute {
+                        name: "format".into(),
+                        value: "hex".into(),
+                        expr: Expr::Identifier("hex".into()),
+                    }],
+                    doc: None,
+                    ..declaration("int", "a")
+                }
+            )
+        );
     }
 
     #[test]
@@ -111,4 +227,163 @@ mod declaration_statement_tests {
         let result = declaration_statement(input);
         assert!(result.is_err());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_declaration_statement_bitfield() {
+        let input = "uint32 flag1 : 1;\nuint32 type : 7 <format=hex>;";
+        let (rest, result) = declaration_statement(input).unwrap();
+        assert_eq!(rest, "uint32 type : 7 <format=hex>;");
+        assert_eq!(
+            result,
+            Declaration {
+                bitfield: Some(Expr::Literal(Literal::Int(1))),
+                ..declaration("uint32", "flag1")
+            }
+        );
+        let (rest, result) = declaration_statement(rest).unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(result.bitfield, Some(Expr::Literal(Literal::Int(7))));
+        assert_eq!(result.attributes.len(), 1);
+    }
+
+    #[test]
+    fn test_declaration_statement_multi_word_type() {
+        let (rest, result) =
+            declaration_statement("unsigned long long size;\nsigned char c;").unwrap();
+        assert_eq!(result, declaration("uint64", "size"));
+        let (_, result) = declaration_statement(rest).unwrap();
+        assert_eq!(result, declaration("char", "c"));
+        assert!(matches!(
+            declaration_statement("unsigned unsigned int a;"),
+            Err(nom::Err::Failure(_))
+        ));
+        assert!(matches!(
+            declaration_statement("short long a;"),
+            Err(nom::Err::Failure(_))
+        ));
+    }
+
+    #[test]
+    fn test_declaration_statement_padding_bitfield() {
+        let (rest, result) = declaration_statement("uint32 : BITS - 1;").unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(result.name, None);
+        assert_eq!(
+            result.bitfield,
+            Some(Expr::BinaryOp {
+                left: Box::new(Expr::Identifier("BITS".into())),
+                op: Expression::Subtract,
+                right: Box::new(Expr::Literal(Literal::Int(1))),
+            })
+        );
+    }
+
+    #[test]
+    fn test_declaration_statement_bitfield_expressions() {
+        let (rest, result) = declaration_statement("uint32 a : 1 << 2;").unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(
+            result.bitfield,
+            Some(Expr::BinaryOp {
+                left: Box::new(Expr::Literal(Literal::Int(1))),
+                op: Expression::BinaryShiftLeft,
+                right: Box::new(Expr::Literal(Literal::Int(2))),
+            })
+        );
+        let (_, result) = declaration_statement("uint32 b : 1 << 2 <format=hex>;").unwrap();
+        assert_eq!(result.attributes.len(), 1);
+        assert!(result.bitfield.is_some());
+        let (_, result) = declaration_statement("uint32 c : (BITS > 8) <format=hex>;").unwrap();
+        assert_eq!(result.attributes.len(), 1);
+        assert!(matches!(result.bitfield, Some(Expr::Parens(_))));
+    }
+
+    #[test]
+    fn test_declaration_list_bitfields() {
+        let (rest, result) = declaration_list("uint32 a : 3, b : 5 <format=hex>, : 8;").unwrap();
+        assert_eq!(rest, "");
+        let fields: Vec<_> = result
+            .iter()
+            .map(|declaration| (declaration.name.as_deref(), declaration.bitfield.clone()))
+            .collect();
+        assert_eq!(
+            fields,
+            vec![
+                (Some("a"), Some(Expr::Literal(Literal::Int(3)))),
+                (Some("b"), Some(Expr::Literal(Literal::Int(5)))),
+                (None, Some(Expr::Literal(Literal::Int(8)))),
+            ]
+        );
+        assert_eq!(result[1].attributes.len(), 1);
+    }
+
+    #[test]
+    fn test_declaration_statement_bitfield_errors() {
+        assert!(declaration_statement("uint32;").is_err());
+        assert!(matches!(
+            declaration_statement("uint32 flag : ;"),
+            Err(nom::Err::Failure(_))
+        ));
+        assert!(matches!(
+            declaration_statement("uint32 flag : 1 2;"),
+            Err(nom::Err::Failure(_))
+        ));
+    }
+
+    #[test]
+    fn test_declaration_statement_arrays() {
+        let (_, result) = declaration_statement("wchar_t CharacterName[0x10];").unwrap();
+        assert_eq!(
+            result,
+            Declaration {
+                args: vec![],
+                dimensions: vec![Some(Expr::Literal(Literal::Int(0x10)))],
+                ..declaration("wchar_t", "CharacterName")
+            }
+        );
+        let (_, result) = declaration_statement("EquipInventoryDataEntry entries[size];").unwrap();
+        assert_eq!(
+            result.dimensions,
+            vec![Some(Expr::Identifier("size".into()))]
+        );
+        let (rest, result) =
+            declaration_statement("ubyte grid[ROWS][COLS * 2] <format=hex>;\nint a;").unwrap();
+        assert_eq!(rest, "int a;");
+        assert_eq!(result.dimensions.len(), 2);
+        assert_eq!(result.attributes.len(), 1);
+        let (_, result) = declaration_statement("char name[ ];").unwrap();
+        assert_eq!(result.dimensions, vec![None]);
+    }
+
+    #[test]
+    fn test_declaration_statement_array_errors() {
+        assert!(matches!(
+            declaration_statement("char name[4;"),
+            Err(nom::Err::Failure(_))
+        ));
+        assert!(declaration_statement("char [4];").is_err());
+        assert!(declaration_statement("char name[4]").is_err());
+    }
+
+    #[test]
+    fn test_declaration_statement_arguments() {
+        let (_, result) = declaration_statement("EquipInventoryData inv(0x780, 0x80);").unwrap();
+        assert_eq!(
+            result,
+            Declaration {
+                args: vec![
+                    Expr::Literal(Literal::Int(0x780)),
+                    Expr::Literal(Literal::Int(0x80)),
+                ],
+                ..declaration("EquipInventoryData", "inv")
+            }
+        );
+        let (_, result) = declaration_statement("Entry e (ver) [count];").unwrap();
+        assert_eq!(result.args, vec![Expr::Identifier("ver".into())]);
+        assert_eq!(
+            result.dimensions,
+            vec![Some(Expr::Identifier("count".into()))]
+        );
+        assert!(declaration_statement("Entry e(ver;").is_err());
+    }
+}
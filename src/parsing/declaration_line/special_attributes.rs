@@ -1,22 +1,15 @@
 use nom::{
-    branch::alt,
-    bytes::complete::tag,
-    character::complete::not_line_ending,
-    error::context,
-    sequence::tuple,
-    IResult,
+    branch::alt, bytes::complete::tag, character::complete::not_line_ending,
+    combinator::all_consuming, error::context, sequence::tuple,
 };
 
-use crate::types::nested::Nested;
+use crate::{ast::Attribute, error::ParseResult, parse_nested::expression};
 
-fn parse_special_attributes(input: &str) -> IResult<&str, (&str, &str)> {
+fn parse_special_attributes(input: &str) -> ParseResult<'_, (&str, &str)> {
     let mut parser = context(
         "special_attributes",
         tuple((
             alt((
-                tag("format="),
-                tag("format="),
-                tag("format="),
                 tag("format="),
                 tag("fgcolor="),
                 tag("bgcolor="),
@@ -43,44 +36,159 @@ fn parse_special_attributes(input: &str) -> IResult<&str, (&str, &str)> {
 /// * `input` - A string slice that holds the input
 ///
 /// # Returns
-/// * `IResult<&str, Nested>`: On success, returns `Ok` wrapping the remaining input
+/// * `ParseResult<'_, Attribute>`: On success, returns `Ok` wrapping the remaining input
+///
+/// The value must be a valid expression, which is stored alongside its text.
 ///
 /// # Example
 ///
 /// ```
-/// use nom::IResult;
+/// use bt_parser::ast::{Attribute, Expr};
 /// use bt_parser::parsing::declaration_line::special_attributes::special_attributes;
-/// use bt_parser::types::nested::Nested;
 ///   
 /// let input = "format=hex";
 /// let result = special_attributes(input).unwrap();
-/// assert_eq!(result, ("", Nested::List(vec![Nested::Text("format=".into()), Nested::Text("hex".into())].into())));
+/// let expr = Expr::Identifier("hex".into());
+/// assert_eq!(result, ("", Attribute { name: "format".into(), value: "hex".into(), expr }));
 /// ```
 ///
 /// # Panics
 /// Panics if the input is not a valid special attribute
 /// One of: [link](https://www.sweetscape.com/010editor/manual/TemplateVariables.htm)
-pub fn special_attributes(input: &str) -> IResult<&str, Nested> {
-    match parse_special_attributes(input) {
-        Ok((rest, result)) => Ok((rest, vec![result.0.into(), result.1.into()].into())),
-        Err(e) => Err(e),
+pub fn special_attributes(input: &str) -> ParseResult<'_, Attribute> {
+    let (rest, (name, value)) = parse_special_attributes(input)?;
+    let value = value.trim();
+    let (_, expr) = all_consuming(expression)(value)?;
+    Ok((
+        rest,
+        Attribute {
+            name: name.trim_end_matches('=').into(),
+            value: value.into(),
+            expr,
+        },
+    ))
+}
+
+/// Finds the `>` closing an attribute list, skipping over quoted strings
+/// and parenthesized expressions such as `(this < 0 ? cRed : cNone)`.
+/// Returns the top-level comma separated pieces and the byte index of `>`.
+fn split_attribute_list(input: &str) -> Option<(Vec<&str>, usize)> {
+    let mut pieces = Vec::new();
+    let mut depth = 0usize;
+    let mut quote = None;
+    let mut escaped = false;
+    let mut start = 1;
+    for (index, character) in input.char_indices().skip(1) {
+        if let Some(open_quote) = quote {
+            match character {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                c if c == open_quote => quote = None,
+                _ => {}
+            }
+            continue;
+        }
+        match character {
+            '"' | '\'' => quote = Some(character),
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth = depth.checked_sub(1)?,
+            ',' if depth == 0 => {
+                pieces.push(&input[start..index]);
+                start = index + 1;
+            }
+            '>' if depth == 0 => {
+                pieces.push(&input[start..index]);
+                return Some((pieces, index));
+            }
+            _ => {}
+        }
     }
+    None
+}
+
+/// Parses a complete `<name=value, ...>` attribute list.
+///
+/// # Example
+///
+/// ```
+/// use bt_parser::ast::Expr;
+/// use bt_parser::parsing::declaration_line::special_attributes::attribute_list;
+///
+/// let (rest, result) = attribute_list("<format=hex, fgcolor=cRed>;").unwrap();
+/// assert_eq!(rest, ";");
+/// assert_eq!(result[1].name, "fgcolor");
+/// assert_eq!(result[1].expr, Expr::Identifier("cRed".into()));
+/// ```
+pub fn attribute_list(input: &str) -> ParseResult<'_, Vec<Attribute>> {
+    let (_, _) = tag("<")(input)?;
+    let (pieces, end) = split_attribute_list(input).ok_or_else(|| {
+        nom::Err::Error(nom::error_position!(
+            input,
+            nom::error::ErrorKind::TagClosure
+        ))
+    })?;
+    let attributes = pieces
+        .into_iter()
+        .map(|piece| match special_attributes(piece.trim()) {
+            Ok((_, attribute)) => Ok(attribute),
+            Err(_) => Err(nom::Err::Error(nom::error_position!(
+                input,
+                nom::error::ErrorKind::Tag
+            ))),
+        })
+        .collect::<Result<Vec<Attribute>, _>>()?;
+    Ok((&input[end + 1..], attributes))
 }
 
 #[cfg(test)]
 mod tests_special_attributes {
     use super::*;
+    use crate::ast::{Expr, Literal};
     use pretty_assertions::assert_eq;
 
+    fn ident(name: &str) -> Box<Expr> {
+        Box::new(Expr::Identifier(name.into()))
+    }
+
+    /// `Str("<%g %g %g>",this[0],this[1],this[2])`
+    pub(super) fn read_expr() -> Expr {
+        let index = |i| Expr::Index {
+            object: ident("this"),
+            index: Box::new(Expr::Literal(Literal::Int(i))),
+        };
+        Expr::FunctionCall {
+            name: "Str".into(),
+            args: vec![
+                Expr::Literal(Literal::String("<%g %g %g>".into())),
+                index(0),
+                index(1),
+                index(2),
+            ],
+        }
+    }
+
+    /// `(this < 0 ? cRed : cNone)`
+    pub(super) fn bgcolor_expr() -> Expr {
+        Expr::Parens(Box::new(Expr::Ternary {
+            condition: Box::new(Expr::BinaryOp {
+                left: ident("this"),
+                op: crate::ast::Expression::LessThan,
+                right: Box::new(Expr::Literal(Literal::Int(0))),
+            }),
+            then_branch: ident("cRed"),
+            else_branch: ident("cNone"),
+        }))
+    }
+
     #[test]
     fn test_special_attributes1() {
         let input = r#"read=Str("<%g %g %g>",this[0],this[1],this[2])"#;
         let expected_rest = "";
-        let expected_result = vec![
-            "read=".into(),
-            r#"Str("<%g %g %g>",this[0],this[1],this[2])"#.into(),
-        ]
-        .into();
+        let expected_result = Attribute {
+            name: "read".into(),
+            value: r#"Str("<%g %g %g>",this[0],this[1],this[2])"#.into(),
+            expr: read_expr(),
+        };
 
         let (rest, result) = special_attributes(input).unwrap();
 
@@ -92,7 +200,11 @@ mod tests_special_attributes {
     fn test_special_attributes2() {
         let input = "bgcolor=(this < 0 ? cRed : cNone )";
         let expected_rest = "";
-        let expected_result = vec!["bgcolor=".into(), "(this < 0 ? cRed : cNone )".into()].into();
+        let expected_result = Attribute {
+            name: "bgcolor".into(),
+            value: "(this < 0 ? cRed : cNone )".into(),
+            expr: bgcolor_expr(),
+        };
 
         let (rest, result) = special_attributes(input).unwrap();
 
@@ -104,15 +216,63 @@ mod tests_special_attributes {
     fn test_special_attributes3() {
         let input = r#"comment="This should be greater than 15.""#;
         let expected_rest = "";
-        let expected_result = vec![
-            "comment=".into(),
-            r#""This should be greater than 15.""#.into(),
-        ]
-        .into();
+        let expected_result = Attribute {
+            name: "comment".into(),
+            value: r#""This should be greater than 15.""#.into(),
+            expr: Expr::Literal(Literal::String("This should be greater than 15.".into())),
+        };
 
         let (rest, result) = special_attributes(input).unwrap();
 
         assert_eq!(rest, expected_rest);
         assert_eq!(result, expected_result);
     }
+
+    #[test]
+    fn test_special_attributes_invalid_value() {
+        assert!(special_attributes("bgcolor=(this <").is_err());
+        assert!(special_attributes("format=hex hex").is_err());
+    }
+}
+
+#[cfg(test)]
+mod tests_attribute_list {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_attribute_list1() {
+        let input = r#"<read=Str("<%g %g %g>",this[0],this[1],this[2]), bgcolor=(this < 0 ? cRed : cNone)> rest"#;
+        let (rest, result) = attribute_list(input).unwrap();
+        assert_eq!(rest, " rest");
+        assert_eq!(
+            result,
+            vec![
+                Attribute {
+                    name: "read".into(),
+                    value: r#"Str("<%g %g %g>",this[0],this[1],this[2])"#.into(),
+                    expr: super::tests_special_attributes::read_expr(),
+                },
+                Attribute {
+                    name: "bgcolor".into(),
+                    value: "(this < 0 ? cRed : cNone)".into(),
+                    expr: super::tests_special_attributes::bgcolor_expr(),
+                },
+            ]
+        );
+    }
+    #[test]
+    fn test_attribute_list2() {
+        assert!(attribute_list("<format=hex").is_err());
+    }
+
+    #[test]
+    fn test_attribute_list3() {
+        assert!(attribute_list("<colour=red>").is_err());
+    }
+
+    #[test]
+    fn test_attribute_list4() {
+        assert!(attribute_list("format=hex>").is_err());
+    }
 }
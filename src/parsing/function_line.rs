@@ -0,0 +1,134 @@
+use nom::character::complete::char;
+
+use super::{
+    comment_line::multispace_or_comment0,
+    parameter_list::parameter_list,
+    statement::{block, identifier},
+    type_specifier::type_specifier,
+};
+use crate::{ast::Function, error::ParseResult};
+
+/// Words that start other statements and so cannot name a function or its
+/// return type.
+const KEYWORDS: [&str; 16] = [
+    "break", "case", "const", "continue", "default", "do", "else", "enum", "for", "if", "local",
+    "return", "struct", "switch", "typedef", "while",
+];
+
+fn not_a_function(input: &str) -> nom::Err<nom::error::VerboseError<&str>> {
+    nom::Err::Error(nom::error_position!(input, nom::error::ErrorKind::Verify))
+}
+
+/// Parses a user function definition such as
+/// `string ReadItemName(ItemEntry &e) { return Str("%08X", e.ItemID); }`,
+/// or a forward declaration ending in `;` instead of a body.
+///
+/// A parenthesized list that is not a parameter list, as in the struct
+/// declaration `Blob b(4);`, is not a function, so another parser can try.
+///
+/// # Example
+///
+/// ```
+/// use bt_parser::parsing::function_line::function_line;
+///
+/// let (_, result) = function_line("void Skip(int &count);").unwrap();
+/// assert_eq!(result.return_type, "void".into());
+/// assert_eq!(result.name, "Skip");
+/// assert_eq!(result.body, None);
+/// ```
+pub fn function_line(input: &str) -> ParseResult<'_, Function> {
+    let (rest, return_type) = type_specifier(input)?;
+    let (rest, name) = identifier(rest)?;
+    if [return_type.name.as_str(), name]
+        .iter()
+        .any(|word| KEYWORDS.contains(word))
+    {
+        return Err(not_a_function(input));
+    }
+    let (rest, params) = parameter_list(rest).map_err(|_| not_a_function(input))?;
+    let (rest, _) = multispace_or_comment0(rest)?;
+    let (rest, body) = match char::<_, ()>(';')(rest) {
+        Ok((rest, _)) => (rest, None),
+        Err(_) => {
+            let (rest, body) = block(rest)?;
+            (rest, Some(body))
+        }
+    };
+    Ok((
+        rest,
+        Function {
+            return_type,
+            name: name.into(),
+            params,
+            body,
+            doc: None,
+        },
+    ))
+}
+
+#[cfg(test)]
+mod function_line_tests {
+    use super::*;
+    use crate::ast::{Expr, Literal, Parameter, Statement};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_function_line1() {
+        let input = r#"string ReadItemName(ItemEntry &e) {
+    return Str("%08X", e.ItemID);
+}
+int a;"#;
+        let (rest, result) = function_line(input).unwrap();
+        assert_eq!(rest, "\nint a;");
+        assert_eq!(result.return_type, "string".into());
+        assert_eq!(result.name, "ReadItemName");
+        assert_eq!(
+            result.params,
+            vec![Parameter {
+                ty: "ItemEntry".into(),
+                name: "e".into(),
+                by_ref: true,
+                is_const: false,
+                is_array: false,
+            }]
+        );
+        match result.body.as_deref() {
+            Some([Statement::Return(Some(Expr::FunctionCall { name, args }))]) => {
+                assert_eq!(name, "Str");
+                assert_eq!(args.len(), 2);
+            }
+            _ => panic!("Expected a single return statement"),
+        }
+    }
+
+    #[test]
+    fn test_function_line2() {
+        let (rest, result) = function_line("unsigned int Count (void) { return 1; }").unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(result.return_type, "uint".into());
+        assert_eq!(result.params, vec![]);
+        assert_eq!(
+            result.body,
+            Some(vec![Statement::Return(Some(Expr::Literal(Literal::Int(
+                1
+            ))))])
+        );
+    }
+
+    #[test]
+    fn test_function_line_not_a_function() {
+        assert!(matches!(
+            function_line("Blob b(4);"),
+            Err(nom::Err::Error(_))
+        ));
+        assert!(matches!(
+            function_line("if (a) { }"),
+            Err(nom::Err::Error(_))
+        ));
+        assert!(matches!(function_line("int a;"), Err(nom::Err::Error(_))));
+        assert!(matches!(
+            function_line("void f() { int a; #bad }"),
+            Err(nom::Err::Failure(_))
+        ));
+    }
+}
@@ -0,0 +1,201 @@
+use nom::{
+    branch::alt, character::complete::char, combinator::opt, error::context, sequence::delimited,
+};
+
+use super::{
+    statement::{body, keyword, symbol},
+    variable_line::variable_line,
+};
+use crate::{
+    ast::{DoWhileStatement, Expr, ForInit, ForStatement, Statement, WhileStatement},
+    error::ParseResult,
+    parse_nested::expression,
+};
+
+fn parenthesized_condition(input: &str) -> ParseResult<'_, Expr> {
+    delimited(symbol('('), expression, char(')'))(input)
+}
+
+fn while_loop(input: &str) -> ParseResult<'_, Statement> {
+    let (rest, _) = keyword("while")(input)?;
+    let (rest, condition) = parenthesized_condition(rest)?;
+    let (rest, body) = body(rest)?;
+    Ok((rest, Statement::While(WhileStatement { condition, body })))
+}
+
+fn do_while_loop(input: &str) -> ParseResult<'_, Statement> {
+    let (rest, _) = keyword("do")(input)?;
+    let (rest, body) = body(rest)?;
+    let (rest, _) = keyword("while")(rest)?;
+    let (rest, condition) = parenthesized_condition(rest)?;
+    let (rest, _) = symbol(';')(rest)?;
+    Ok((
+        rest,
+        Statement::DoWhile(DoWhileStatement { body, condition }),
+    ))
+}
+
+/// The first clause of a `for` loop up to and including its `;`.
+fn for_init(input: &str) -> ParseResult<'_, Option<ForInit>> {
+    match variable_line(input) {
+        Ok((rest, variable)) => return Ok((rest, Some(ForInit::Variable(variable)))),
+        Err(nom::Err::Error(_)) => {}
+        Err(error) => return Err(error),
+    }
+    let (rest, init) = opt(expression)(input)?;
+    let (rest, _) = symbol(';')(rest)?;
+    Ok((rest, init.map(ForInit::Expression)))
+}
+
+fn for_loop(input: &str) -> ParseResult<'_, Statement> {
+    let (rest, _) = keyword("for")(input)?;
+    let (rest, _) = symbol('(')(rest)?;
+    let (rest, init) = for_init(rest)?;
+    let (rest, condition) = opt(expression)(rest)?;
+    let (rest, _) = symbol(';')(rest)?;
+    let (rest, step) = opt(expression)(rest)?;
+    let (rest, _) = symbol(')')(rest)?;
+    let (rest, body) = body(rest)?;
+    Ok((
+        rest,
+        Statement::For(ForStatement {
+            init,
+            condition,
+            step,
+            body,
+        }),
+    ))
+}
+
+/// Parses a `while`, `do ... while` or `for` loop together with its body,
+/// which is either a `{ ... }` block or a single statement.
+///
+/// # Example
+///
+/// ```
+/// use bt_parser::ast::Statement;
+/// use bt_parser::parsing::loop_line::loop_statement;
+///
+/// let (rest, result) = loop_statement("while (!FEof()) { Entry e; }").unwrap();
+/// assert_eq!(rest, "");
+/// match result {
+///     Statement::While(statement) => assert_eq!(statement.body.len(), 1),
+///     _ => panic!("Expected Statement::While"),
+/// }
+/// ```
+pub fn loop_statement(input: &str) -> ParseResult<'_, Statement> {
+    context("loop", alt((while_loop, do_while_loop, for_loop)))(input)
+}
+
+#[cfg(test)]
+mod loop_statement_tests {
+    use super::*;
+    use crate::ast::{Declarator, Expression, Literal, Storage, Variable};
+    use pretty_assertions::assert_eq;
+
+    fn identifier(name: &str) -> Expr {
+        Expr::Identifier(name.into())
+    }
+
+    #[test]
+    fn test_for_loop() {
+        let input = r#"for (i = 0; i < count; i++) {
+    Entry entries;
+    if (i > 10) {
+        break;
+    }
+}
+int a;"#;
+        let (rest, result) = loop_statement(input).unwrap();
+        assert_eq!(rest, "\nint a;");
+        let Statement::For(ForStatement {
+            init,
+            condition,
+            step,
+            body,
+        }) = result
+        else {
+            panic!("Expected Statement::For");
+        };
+        assert_eq!(
+            init,
+            Some(ForInit::Expression(Expr::Assign {
+                target: Box::new(identifier("i")),
+                op: Expression::Assign,
+                value: Box::new(Expr::Literal(Literal::Int(0))),
+            }))
+        );
+        assert_eq!(
+            condition,
+            Some(Expr::BinaryOp {
+                left: Box::new(identifier("i")),
+                op: Expression::LessThan,
+                right: Box::new(identifier("count")),
+            })
+        );
+        assert!(step.is_some());
+        match &body[..] {
+            [Statement::Declaration(_), Statement::If(statement)] => {
+                assert_eq!(statement.body, vec![Statement::Break])
+            }
+            _ => panic!("Unexpected loop body {:?}", body),
+        }
+    }
+
+    #[test]
+    fn test_for_loop_local_variable() {
+        let (rest, result) =
+            loop_statement("for (local int i = 0; i < 3; i++) {\n    Entry e;\n}").unwrap();
+        assert_eq!(rest, "");
+        let Statement::For(ForStatement { init, body, .. }) = result else {
+            panic!("Expected Statement::For");
+        };
+        assert_eq!(
+            init,
+            Some(ForInit::Variable(Variable {
+                storage: Storage::Local,
+                ty: "int".into(),
+                declarators: vec![Declarator {
+                    name: "i".into(),
+                    dimensions: vec![],
+                    init: Some(Expr::Literal(Literal::Int(0))),
+                }],
+                doc: None,
+            }))
+        );
+        assert_eq!(body.len(), 1);
+        assert!(loop_statement("for (local int; i < 3; i++) {}").is_err());
+    }
+
+    #[test]
+    fn test_for_loop_empty_clauses() {
+        let (_, result) = loop_statement("for (;;) continue;").unwrap();
+        assert_eq!(
+            result,
+            Statement::For(ForStatement {
+                init: None,
+                condition: None,
+                step: None,
+                body: vec![Statement::Continue],
+            })
+        );
+    }
+
+    #[test]
+    fn test_while_loops() {
+        let (rest, result) = loop_statement("while (n) n--;").unwrap();
+        assert_eq!(rest, "");
+        assert!(matches!(result, Statement::While(statement) if statement.body.len() == 1));
+
+        let (rest, result) = loop_statement("do {\n    Chunk c;\n} while (!FEof());").unwrap();
+        assert_eq!(rest, "");
+        assert!(matches!(result, Statement::DoWhile(statement) if statement.body.len() == 1));
+    }
+
+    #[test]
+    fn test_loop_errors() {
+        assert!(loop_statement("whilex (a) {}").is_err());
+        assert!(loop_statement("do { } while (a)").is_err());
+        assert!(loop_statement("for (i = 0; i < 2) {}").is_err());
+    }
+}
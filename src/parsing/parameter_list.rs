@@ -0,0 +1,133 @@
+use nom::{
+    combinator::opt,
+    multi::separated_list0,
+    sequence::{pair, tuple},
+};
+
+use super::{
+    statement::{identifier, keyword, symbol},
+    type_specifier::type_specifier,
+};
+use crate::{
+    ast::Parameter,
+    error::{failure, ParseResult},
+};
+
+/// One parameter, e.g. `const unsigned int &count` or `uchar data[]`.
+///
+/// Multi-word types such as `unsigned int` become their built-in type, here
+/// `uint`. A leading `const` is recorded separately.
+fn parameter(input: &str) -> ParseResult<'_, Parameter> {
+    let (rest, is_const) = opt(keyword("const"))(input)?;
+    let (rest, ty) = type_specifier(rest)?;
+    let (rest, by_ref) = opt(symbol('&'))(rest)?;
+    let Ok((rest, name)) = identifier(rest) else {
+        return failure(input, "expected a parameter type and name");
+    };
+    let (rest, is_array) = opt(pair(symbol('['), symbol(']')))(rest)?;
+    Ok((
+        rest,
+        Parameter {
+            ty,
+            name: name.into(),
+            by_ref: by_ref.is_some(),
+            is_const: is_const.is_some(),
+            is_array: is_array.is_some(),
+        },
+    ))
+}
+
+/// Parses the parenthesized parameters of a struct typedef or a function,
+/// e.g. `(int &count, const uint32 flags, uchar data[])`. Both `()` and
+/// `(void)` declare no parameters.
+///
+/// # Example
+///
+/// ```
+/// use bt_parser::parsing::parameter_list::parameter_list;
+///
+/// let (_, params) = parameter_list("(int &count, unsigned int size, uchar data[])").unwrap();
+/// assert_eq!(params[0].name, "count");
+/// assert!(params[0].by_ref);
+/// assert_eq!(params[1].ty, "uint".into());
+/// assert!(params[2].is_array);
+/// ```
+pub fn parameter_list(input: &str) -> ParseResult<'_, Vec<Parameter>> {
+    let (rest, _) = symbol('(')(input)?;
+    let mut void = tuple((keyword("void"), symbol(')')));
+    if let Ok((rest, _)) = void(rest) {
+        return Ok((rest, Vec::new()));
+    }
+    let (rest, params) = separated_list0(symbol(','), parameter)(rest)?;
+    match symbol(')')(rest) {
+        Ok((rest, _)) => Ok((rest, params)),
+        Err(_) => failure(rest, "expected `,` or `)` in the parameter list"),
+    }
+}
+
+#[cfg(test)]
+mod parameter_list_tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn param(ty: &str, name: &str) -> Parameter {
+        Parameter {
+            ty: ty.into(),
+            name: name.into(),
+            by_ref: false,
+            is_const: false,
+            is_array: false,
+        }
+    }
+
+    #[test]
+    fn test_parameter_list1() {
+        let (rest, params) =
+            parameter_list("(int &count, const uint32 flags, uchar data[ ]) {").unwrap();
+        assert_eq!(rest, " {");
+        assert_eq!(
+            params,
+            vec![
+                Parameter {
+                    by_ref: true,
+                    ..param("int", "count")
+                },
+                Parameter {
+                    is_const: true,
+                    ..param("uint32", "flags")
+                },
+                Parameter {
+                    is_array: true,
+                    ..param("uchar", "data")
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parameter_list2() {
+        let (_, params) = parameter_list("( unsigned int size,ItemEntry & e )").unwrap();
+        assert_eq!(
+            params,
+            vec![
+                param("uint", "size"),
+                Parameter {
+                    by_ref: true,
+                    ..param("ItemEntry", "e")
+                },
+            ]
+        );
+        assert_eq!(parameter_list("()").unwrap().1, vec![]);
+        assert_eq!(parameter_list("( void )").unwrap().1, vec![]);
+    }
+
+    #[test]
+    fn test_parameter_list_errors() {
+        assert!(matches!(parameter_list("(int)"), Err(nom::Err::Failure(_))));
+        assert!(matches!(
+            parameter_list("(int a; int b)"),
+            Err(nom::Err::Failure(_))
+        ));
+        assert!(parameter_list("int a").is_err());
+    }
+}
@@ -1,15 +1,41 @@
-use nom::IResult;
+use crate::error::ParseResult;
 
-pub fn parse_brackets(input: &str) -> IResult<&str, &str> {
+/// Length of the comment or quoted literal at the start of `input`, so that
+/// brackets inside it are not counted. Unterminated ones run to the end.
+fn skipped_len(input: &str) -> Option<usize> {
+    if input.starts_with("//") {
+        return Some(input.find('\n').unwrap_or(input.len()));
+    }
+    if let Some(body) = input.strip_prefix("/*") {
+        return Some(body.find("*/").map_or(input.len(), |end| end + 4));
+    }
+    let quote = input.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+    let mut escaped = false;
+    for (index, character) in input.char_indices().skip(1) {
+        match character {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            c if c == quote => return Some(index + 1),
+            _ => {}
+        }
+    }
+    Some(input.len())
+}
+
+pub fn parse_brackets(input: &str) -> ParseResult<'_, &str> {
     let mut stack = Vec::new();
     let mut start_index = None;
     let mut contents = "";
     let mut rest = "";
-    let mut is_nested = false;
     let mut open_bracket_idx = None;
     let mut closed_bracket_idx = None;
 
+    let mut skip_to = 0;
+
     for (index, character) in input.char_indices() {
+        if index < skip_to {
+            continue;
+        }
         match character {
             '{' => {
                 open_bracket_idx = Some(index);
@@ -17,11 +43,10 @@ pub fn parse_brackets(input: &str) -> IResult<&str, &str> {
                     start_index = Some(index);
                 }
                 stack.push(character);
-                is_nested = false;
             }
             '}' => {
                 closed_bracket_idx = Some(index);
-                if let Some(_) = stack.pop() {
+                if stack.pop().is_some() {
                     if stack.is_empty() {
                         if let Some(start) = start_index {
                             // Capture the expression excluding the outermost brackets
@@ -39,7 +64,11 @@ pub fn parse_brackets(input: &str) -> IResult<&str, &str> {
                         nom::error::ErrorKind::Fail
                     )));
                 }
-                is_nested = true;
+            }
+            '/' | '"' | '\'' if !stack.is_empty() => {
+                if let Some(len) = skipped_len(&input[index..]) {
+                    skip_to = index + len;
+                }
             }
             _ => {
                 if open_bracket_idx.is_none() && closed_bracket_idx.is_none() {
@@ -53,7 +82,7 @@ pub fn parse_brackets(input: &str) -> IResult<&str, &str> {
     }
 
     if stack.is_empty() {
-        let rest = if rest.starts_with(";") {
+        let rest = if rest.starts_with(';') {
             let range = 1..rest.len();
             rest[range].trim()
         } else {
@@ -158,4 +187,15 @@ mod tests_parse_nested_brackets {
         assert_eq!(rest, expected_rest);
         assert_eq!(result, expected_result);
     }
+
+    #[test]
+    fn test_parse_nested_brackets_skips_comments_and_strings() {
+        let input = r#"{
+        int a; // closes with }
+        /* { */ char b<comment="}">;
+    } rest"#;
+        let (rest, result) = parse_brackets(input).unwrap();
+        assert_eq!(rest, "rest");
+        assert!(result.ends_with("char b<comment=\"}\">;\n    }"));
+    }
 }
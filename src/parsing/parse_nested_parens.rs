@@ -1,6 +1,6 @@
-use nom::IResult;
+use crate::error::ParseResult;
 
-pub fn parse_nested_parens(input: &str) -> IResult<&str, Vec<&str>> {
+pub fn parse_nested_parens(input: &str) -> ParseResult<'_, Vec<&str>> {
     let mut stack = Vec::new();
     let mut start_index = None;
     let mut nested_expressions = Vec::new();
@@ -21,7 +21,7 @@ pub fn parse_nested_parens(input: &str) -> IResult<&str, Vec<&str>> {
             }
             ')' => {
                 closed_paren_idx = Some(index);
-                if let Some(_) = stack.pop() {
+                if stack.pop().is_some() {
                     if stack.is_empty() {
                         if let Some(start) = start_index {
                             // Capture the expression excluding the outermost parentheses
@@ -0,0 +1,580 @@
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    bytes::complete::take_while,
+    character::complete::{char, satisfy},
+    combinator::{map, not, opt, peek, recognize, value},
+    error::context,
+    sequence::{pair, preceded, terminated},
+};
+
+use crate::{
+    ast::Statement,
+    error::{failure, ParseResult},
+    parse_nested::expression,
+};
+
+use super::{
+    comment_line::{comment_line, multispace_or_comment0, trailing_comment},
+    conditional_line,
+    declaration_line::{declaration_list, declaration_statement},
+    function_line::function_line,
+    loop_line::loop_statement,
+    switch_line::switch_statement,
+    typedef_line::{
+        inline_declaration::inline_declaration,
+        typedef_alias::typedef_alias,
+        typedef_enum::typedef_enum,
+        typedef_line,
+        typedef_union::{typedef_union, union_declaration},
+    },
+    variable_line::variable_line,
+};
+
+fn comment_statement(input: &str) -> ParseResult<'_, Statement> {
+    map(comment_line, |(_, text)| Statement::Comment(text.into()))(input)
+}
+
+fn typedef_statement(input: &str) -> ParseResult<'_, Statement> {
+    map(typedef_line, Statement::Typedef)(input)
+}
+
+fn typedef_enum_statement(input: &str) -> ParseResult<'_, Statement> {
+    map(typedef_enum, Statement::TypedefEnum)(input)
+}
+
+fn typedef_union_statement(input: &str) -> ParseResult<'_, Statement> {
+    map(typedef_union, Statement::TypedefUnion)(input)
+}
+
+fn typedef_alias_statement(input: &str) -> ParseResult<'_, Statement> {
+    map(typedef_alias, Statement::TypedefAlias)(input)
+}
+
+fn inline_statement(input: &str) -> ParseResult<'_, Statement> {
+    map(inline_declaration, Statement::Inline)(input)
+}
+
+fn union_statement(input: &str) -> ParseResult<'_, Statement> {
+    map(union_declaration, Statement::Union)(input)
+}
+
+fn if_statement(input: &str) -> ParseResult<'_, Statement> {
+    map(conditional_line::if_statement, Statement::If)(input)
+}
+
+fn declaration(input: &str) -> ParseResult<'_, Statement> {
+    map(declaration_statement, Statement::Declaration)(input)
+}
+
+fn variable_statement(input: &str) -> ParseResult<'_, Statement> {
+    map(variable_line, Statement::Variable)(input)
+}
+
+fn function_statement(input: &str) -> ParseResult<'_, Statement> {
+    map(function_line, Statement::Function)(input)
+}
+
+fn return_statement(input: &str) -> ParseResult<'_, Statement> {
+    let (rest, _) = keyword("return")(input)?;
+    let (rest, value) = opt(expression)(rest)?;
+    let (rest, _) = preceded(multispace_or_comment0, char(';'))(rest)?;
+    Ok((rest, Statement::Return(value)))
+}
+
+fn jump_statement(input: &str) -> ParseResult<'_, Statement> {
+    terminated(
+        alt((
+            value(Statement::Break, keyword("break")),
+            value(Statement::Continue, keyword("continue")),
+        )),
+        preceded(multispace_or_comment0, char(';')),
+    )(input)
+}
+
+fn expression_statement(input: &str) -> ParseResult<'_, Statement> {
+    let (rest, value) = expression(input)?;
+    let (rest, _) = char(';')(rest)?;
+    Ok((rest, Statement::Expression(value)))
+}
+
+/// Matches `word` as a whole word, so `do` does not match the start of
+/// `double`.
+pub fn keyword<'a>(word: &'static str) -> impl FnMut(&'a str) -> ParseResult<'a, &'a str> {
+    preceded(
+        multispace_or_comment0,
+        terminated(
+            tag(word),
+            not(peek(satisfy(|c: char| c.is_alphanumeric() || c == '_'))),
+        ),
+    )
+}
+
+/// Matches a C identifier such as `_Header2`, which cannot start with a
+/// digit.
+pub fn identifier(input: &str) -> ParseResult<'_, &str> {
+    preceded(
+        multispace_or_comment0,
+        recognize(pair(
+            satisfy(|c: char| c.is_ascii_alphabetic() || c == '_'),
+            take_while(|c: char| c.is_ascii_alphanumeric() || c == '_'),
+        )),
+    )(input)
+}
+
+/// Matches the punctuation `c`, e.g. `symbol(';')`.
+pub fn symbol<'a>(c: char) -> impl FnMut(&'a str) -> ParseResult<'a, char> {
+    preceded(multispace_or_comment0, char(c))
+}
+
+/// Fails at a `case` or `default` label, which ends the statements of the
+/// previous switch case.
+fn no_case_label(input: &str) -> ParseResult<'_, ()> {
+    not(alt((keyword("case"), keyword("default"))))(input)
+}
+
+/// Parses a single top-level statement, dispatching on its leading keyword.
+///
+/// # Example
+///
+/// ```
+/// use bt_parser::ast::Statement;
+/// use bt_parser::parsing::statement::statement;
+///
+/// let (rest, result) = statement("// header\nint a;").unwrap();
+/// assert_eq!(rest, "\nint a;");
+/// assert_eq!(result, Statement::Comment("header".into()));
+/// ```
+pub fn statement(input: &str) -> ParseResult<'_, Statement> {
+    let input = input.trim_start();
+    let (_, _) = no_case_label(input)?;
+    context(
+        "statement",
+        alt((
+            comment_statement,
+            typedef_statement,
+            typedef_enum_statement,
+            typedef_union_statement,
+            typedef_alias_statement,
+            inline_statement,
+            union_statement,
+            if_statement,
+            loop_statement,
+            switch_statement,
+            return_statement,
+            jump_statement,
+            variable_statement,
+            function_statement,
+            declaration,
+            expression_statement,
+        )),
+    )(input)
+}
+
+/// Parses one statement like [`statement`], except that a declaration of
+/// several names such as `uint16 x, y;` becomes one
+/// [`Statement::Declaration`] per name.
+pub fn statements(input: &str) -> ParseResult<'_, Vec<Statement>> {
+    match statement(input) {
+        Ok((rest, item)) => Ok((rest, vec![item])),
+        Err(nom::Err::Error(error)) => match preceded(no_case_label, declaration_list)(input) {
+            Ok((rest, declarations)) => Ok((
+                rest,
+                declarations
+                    .into_iter()
+                    .map(Statement::Declaration)
+                    .collect(),
+            )),
+            Err(nom::Err::Failure(error)) => Err(nom::Err::Failure(error)),
+            Err(_) => Err(nom::Err::Error(error)),
+        },
+        Err(error) => Err(error),
+    }
+}
+
+/// The typedef or declaration field a comment can be attached to.
+fn doc_slot(statement: &mut Statement) -> Option<&mut Option<String>> {
+    match statement {
+        Statement::Typedef(typedef) => Some(&mut typedef.doc),
+        Statement::TypedefEnum(typedef) => Some(&mut typedef.doc),
+        Statement::TypedefUnion(typedef) => Some(&mut typedef.doc),
+        Statement::TypedefAlias(typedef) => Some(&mut typedef.doc),
+        Statement::Union(union) => Some(&mut union.doc),
+        Statement::Inline(inline) => Some(&mut inline.doc),
+        Statement::Declaration(declaration) => Some(&mut declaration.doc),
+        Statement::Variable(variable) => Some(&mut variable.doc),
+        Statement::Function(function) => Some(&mut function.doc),
+        _ => None,
+    }
+}
+
+/// Parses statements until the input is exhausted or the next one cannot be
+/// recognized, which is left in the remaining input.
+///
+/// Comments directly above a typedef or declaration, with no blank line in
+/// between, and a comment trailing it on the same line become its `doc`,
+/// shared by every name of a declaration such as `uint16 x, y;`. Every other
+/// comment is kept as a [`Statement::Comment`].
+///
+/// # Example
+///
+/// ```
+/// use bt_parser::ast::Statement;
+/// use bt_parser::parsing::statement::statement_list;
+///
+/// let (rest, items) = statement_list("// header\n\n// Item count\nint a; // u32\n}").unwrap();
+/// assert_eq!(rest, "}");
+/// assert_eq!(items[0], Statement::Comment("header".into()));
+/// match &items[1] {
+///     Statement::Declaration(declaration) => {
+///         assert_eq!(declaration.doc.as_deref(), Some("Item count\nu32"));
+///     }
+///     _ => panic!("Expected Statement::Declaration"),
+/// }
+/// ```
+pub fn statement_list(input: &str) -> ParseResult<'_, Vec<Statement>> {
+    let mut items = Vec::new();
+    let mut pending: Vec<String> = Vec::new();
+    let mut rest = input;
+    loop {
+        let trimmed = rest.trim_start();
+        if rest[..rest.len() - trimmed.len()].matches('\n').count() > 1 {
+            items.extend(pending.drain(..).map(Statement::Comment));
+        }
+        rest = trimmed;
+        if rest.is_empty() {
+            break;
+        }
+        let mut group = match statements(rest) {
+            Ok((next, group)) if next.len() < rest.len() => {
+                rest = next;
+                group
+            }
+            Err(nom::Err::Failure(error)) => return Err(nom::Err::Failure(error)),
+            Ok(_) | Err(_) => break,
+        };
+        if let [Statement::Comment(text)] = group.as_mut_slice() {
+            pending.push(std::mem::take(text));
+            continue;
+        }
+        let trailing = trailing_comment(rest).map(|(next, text)| {
+            rest = next;
+            text.to_string()
+        });
+        let count = group.len();
+        let mut docs: Vec<_> = group.iter_mut().filter_map(doc_slot).collect();
+        if docs.len() == count {
+            let lines: Vec<String> = pending.drain(..).chain(trailing).collect();
+            if !lines.is_empty() {
+                for doc in docs.iter_mut() {
+                    **doc = Some(lines.join("\n"));
+                }
+            }
+            items.extend(group);
+        } else {
+            items.extend(pending.drain(..).map(Statement::Comment));
+            items.extend(group);
+            items.extend(trailing.map(Statement::Comment));
+        }
+    }
+    items.extend(pending.into_iter().map(Statement::Comment));
+    Ok((rest, items))
+}
+
+/// Parses a `{ ... }` block such as a struct body into its statements,
+/// consuming the closing `}`. Anything inside that is not a statement is a
+/// hard error.
+///
+/// # Example
+///
+/// ```
+/// use bt_parser::parsing::statement::block;
+///
+/// let (rest, items) = block("{\n    int a;\n    if (a) { int b; }\n} Foo;").unwrap();
+/// assert_eq!(rest, " Foo;");
+/// assert_eq!(items.len(), 2);
+/// ```
+pub fn block(input: &str) -> ParseResult<'_, Vec<Statement>> {
+    let (rest, _) = preceded(multispace_or_comment0, char('{'))(input)?;
+    let (rest, items) = statement_list(rest)?;
+    let (rest, _) = multispace_or_comment0(rest)?;
+    match char::<_, ()>('}')(rest) {
+        Ok((rest, _)) => Ok((rest, items)),
+        Err(_) => failure(rest, "expected a statement or `}`"),
+    }
+}
+
+/// Parses the body of a loop or conditional: a `{ ... }` block, or else a
+/// single statement.
+pub fn body(input: &str) -> ParseResult<'_, Vec<Statement>> {
+    let (rest, _) = multispace_or_comment0(input)?;
+    if rest.starts_with('{') {
+        return block(rest);
+    }
+    statements(rest)
+}
+
+#[cfg(test)]
+mod statement_tests {
+    use super::*;
+    use crate::ast::{Declaration, Expr, Expression, IfStatement, InlineType, Literal, Storage};
+    use pretty_assertions::assert_eq;
+
+    fn declaration(ty: &str, name: &str) -> Statement {
+        Statement::Declaration(Declaration {
+            ty: ty.into(),
+            name: Some(name.into()),
+            args: vec![],
+            dimensions: vec![],
+            bitfield: None,
+            attributes: vec![],
+            doc: None,
+        })
+    }
+
+    #[test]
+    fn test_identifier() {
+        assert_eq!(identifier(" _Header2 h;"), Ok((" h;", "_Header2")));
+        assert!(identifier("9abc;").is_err());
+        assert!(statement("int 9abc;").is_err());
+    }
+
+    #[test]
+    fn test_statement1() {
+        let input = r#"typedef struct {
+  wchar_t  CharacterName[0x10];
+} PlayerGameData <size=0x1B0>;
+int a;"#;
+        let (rest, result) = statement(input).unwrap();
+        assert_eq!(rest, "\nint a;");
+        match result {
+            Statement::Typedef(typedef) => assert_eq!(typedef.alias, "PlayerGameData"),
+            _ => panic!("Expected Statement::Typedef"),
+        }
+    }
+
+    #[test]
+    fn test_statement2() {
+        let input = r#"typedef struct (int size, int size2) {
+  EquipInventoryDataEntry CharacterName[size];
+} EquipInventoryData;
+int a;"#;
+        let (rest, result) = statement(input).unwrap();
+        assert_eq!(rest, "\nint a;");
+        match result {
+            Statement::Typedef(typedef) => {
+                assert_eq!(typedef.params.len(), 2);
+                assert_eq!(typedef.body.len(), 1);
+                assert_eq!(typedef.alias, "EquipInventoryData");
+            }
+            _ => panic!("Expected Statement::Typedef"),
+        }
+    }
+
+    #[test]
+    fn test_statement3() {
+        let input = r#"if (ItemID != 0) {
+    int32 unk;
+}
+int a;"#;
+        let (rest, result) = statement(input).unwrap();
+        assert_eq!(rest, "\nint a;");
+        assert_eq!(
+            result,
+            Statement::If(IfStatement {
+                condition: Expr::BinaryOp {
+                    left: Box::new(Expr::Identifier("ItemID".into())),
+                    op: Expression::NotEquals,
+                    right: Box::new(Expr::Literal(Literal::Int(0))),
+                },
+                body: vec![declaration("int32", "unk")],
+                else_ifs: vec![],
+                else_body: None,
+            })
+        );
+    }
+
+    #[test]
+    fn test_statement4() {
+        let (rest, result) = statement("  int a;").unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(result, declaration("int", "a"));
+    }
+
+    #[test]
+    fn test_statement5() {
+        assert!(statement("#include \"other.bt\"").is_err());
+    }
+
+    #[test]
+    fn test_statement6() {
+        let (rest, items) =
+            statement_list("// Item kinds\ntypedef enum <uchar> { A, B } KIND;").unwrap();
+        assert_eq!(rest, "");
+        match &items[..] {
+            [Statement::TypedefEnum(typedef)] => {
+                assert_eq!(typedef.alias, "KIND");
+                assert_eq!(typedef.doc.as_deref(), Some("Item kinds"));
+            }
+            _ => panic!("Expected a single Statement::TypedefEnum"),
+        }
+    }
+
+    #[test]
+    fn test_statement7() {
+        let input = "typedef union { uint32 raw; float f; } Value;\nunion { int a; } u; // inline\nunion { int b; };";
+        let (rest, items) = statement_list(input).unwrap();
+        assert_eq!(rest, "");
+        match &items[..] {
+            [Statement::TypedefUnion(typedef), Statement::Inline(inline), Statement::Union(union)] =>
+            {
+                assert_eq!(typedef.alias, "Value");
+                assert_eq!(
+                    typedef.body,
+                    vec![declaration("uint32", "raw"), declaration("float", "f")]
+                );
+                assert_eq!(inline.ty.alias(), "union@u");
+                assert_eq!(inline.doc.as_deref(), Some("inline"));
+                assert_eq!(union.variable, None);
+            }
+            _ => panic!("Unexpected statements {:?}", items),
+        }
+    }
+
+    #[test]
+    fn test_statement_nested_body() {
+        let input = r#"typedef struct {
+    // Header
+    int32 count;
+    typedef struct { ubyte b; } Inner;
+    if (count > 0) {
+        int32 first; // first entry
+    }
+} Outer;"#;
+        let (rest, result) = statement(input).unwrap();
+        assert_eq!(rest, "");
+        let Statement::Typedef(typedef) = result else {
+            panic!("Expected Statement::Typedef");
+        };
+        match &typedef.body[..] {
+            [Statement::Declaration(count), Statement::Typedef(inner), Statement::If(IfStatement { body, .. })] =>
+            {
+                assert_eq!(count.doc.as_deref(), Some("Header"));
+                assert_eq!(inner.body, vec![declaration("ubyte", "b")]);
+                match &body[..] {
+                    [Statement::Declaration(first)] => {
+                        assert_eq!(first.doc.as_deref(), Some("first entry"))
+                    }
+                    _ => panic!("Expected a single Statement::Declaration"),
+                }
+            }
+            _ => panic!("Expected a declaration, a typedef and a conditional"),
+        }
+    }
+
+    #[test]
+    fn test_statement_body_errors() {
+        assert!(matches!(
+            statement("typedef struct { int a; #bad } S;"),
+            Err(nom::Err::Failure(_))
+        ));
+        assert!(matches!(
+            statement("if (a) { int b;"),
+            Err(nom::Err::Failure(_))
+        ));
+    }
+
+    #[test]
+    fn test_statement_inline_declarations() {
+        let input = r#"typedef struct {
+    // Position
+    struct { uint16 x, y; } pos;
+    enum <ubyte> { OFF, ON } state;
+    uint16 w, h; // size
+} Widget;"#;
+        let (rest, result) = statement(input).unwrap();
+        assert_eq!(rest, "");
+        let Statement::Typedef(typedef) = result else {
+            panic!("Expected Statement::Typedef");
+        };
+        match &typedef.body[..] {
+            [Statement::Inline(pos), Statement::Inline(state), Statement::Declaration(w), Statement::Declaration(h)] =>
+            {
+                assert_eq!(pos.doc.as_deref(), Some("Position"));
+                assert!(matches!(&pos.ty, InlineType::Struct(s) if s.body.len() == 2));
+                assert_eq!(state.declarations[0].ty, "enum@state".into());
+                assert_eq!(w.name.as_deref(), Some("w"));
+                assert_eq!(w.doc.as_deref(), Some("size"));
+                assert_eq!(h.doc.as_deref(), Some("size"));
+            }
+            _ => panic!("Unexpected struct body {:?}", typedef.body),
+        }
+        assert!(matches!(
+            statements("uint16 x, ;"),
+            Err(nom::Err::Failure(_))
+        ));
+    }
+
+    #[test]
+    fn test_statement_variable() {
+        let input =
+            "// Expected header\nconst uint MAGIC = 0x4D534742;\nlocal int i = 0;\nint32 magic;";
+        let (rest, items) = statement_list(input).unwrap();
+        assert_eq!(rest, "");
+        match &items[..] {
+            [Statement::Variable(magic), Statement::Variable(i), Statement::Declaration(_)] => {
+                assert_eq!(magic.storage, Storage::Const);
+                assert_eq!(magic.doc.as_deref(), Some("Expected header"));
+                assert_eq!(i.storage, Storage::Local);
+                assert_eq!(i.declarators[0].init, Some(Expr::Literal(Literal::Int(0))));
+            }
+            _ => panic!("Unexpected statements {:?}", items),
+        }
+    }
+
+    #[test]
+    fn test_statement_function() {
+        let input = r#"// Display name
+string ReadItemName(ItemEntry &e) {
+    local_count++;
+    Printf("%d", e.ItemID);
+    return;
+}
+void Skip(int n);"#;
+        let (rest, items) = statement_list(input).unwrap();
+        assert_eq!(rest, "");
+        match &items[..] {
+            [Statement::Function(read), Statement::Function(skip)] => {
+                assert_eq!(read.doc.as_deref(), Some("Display name"));
+                match read.body.as_deref() {
+                    Some(
+                        [Statement::Expression(Expr::PostfixOp { .. }), Statement::Expression(Expr::FunctionCall { .. }), Statement::Return(None)],
+                    ) => {}
+                    body => panic!("Unexpected function body {:?}", body),
+                }
+                assert_eq!(skip.body, None);
+            }
+            _ => panic!("Expected two Statement::Function"),
+        }
+    }
+
+    #[test]
+    fn test_statement_expression() {
+        let (rest, result) = statement("Blob b(4);\nFSkip(4);").unwrap();
+        assert!(matches!(result, Statement::Declaration(_)));
+        let (_, result) = statement(rest).unwrap();
+        assert!(matches!(
+            result,
+            Statement::Expression(Expr::FunctionCall { .. })
+        ));
+        assert_eq!(
+            statement("return x + 1;").unwrap().1,
+            Statement::Return(Some(Expr::BinaryOp {
+                left: Box::new(Expr::Identifier("x".into())),
+                op: Expression::Add,
+                right: Box::new(Expr::Literal(Literal::Int(1))),
+            }))
+        );
+        assert!(statement("returned;").is_ok());
+    }
+}
@@ -0,0 +1,116 @@
+use nom::{
+    branch::alt,
+    character::complete::char,
+    combinator::map,
+    sequence::{delimited, preceded, terminated},
+};
+
+use super::statement::{keyword, statement_list, symbol};
+use crate::{
+    ast::{CaseLabel, Statement, SwitchCase, SwitchStatement},
+    error::{failure, ParseResult},
+    parse_nested::{constant_expression, expression},
+};
+
+fn case_label(input: &str) -> ParseResult<'_, CaseLabel> {
+    terminated(
+        alt((
+            map(
+                preceded(keyword("case"), constant_expression),
+                CaseLabel::Case,
+            ),
+            map(keyword("default"), |_| CaseLabel::Default),
+        )),
+        symbol(':'),
+    )(input)
+}
+
+/// Parses `switch (value) { case A: ... default: ... }`.
+///
+/// Each label starts a [`SwitchCase`] holding the statements up to the next
+/// label. A case without a `break`, including one with no statements such as
+/// `case 1: case 2: ...`, falls through to the next.
+///
+/// # Example
+///
+/// ```
+/// use bt_parser::ast::{CaseLabel, Statement};
+/// use bt_parser::parsing::switch_line::switch_statement;
+///
+/// let input = "switch (ItemType) { case WEAPON: Weapon w; break; default: break; }";
+/// let (_, result) = switch_statement(input).unwrap();
+/// match result {
+///     Statement::Switch(statement) => {
+///         assert_eq!(statement.cases.len(), 2);
+///         assert_eq!(statement.cases[1].label, CaseLabel::Default);
+///     }
+///     _ => panic!("Expected Statement::Switch"),
+/// }
+/// ```
+pub fn switch_statement(input: &str) -> ParseResult<'_, Statement> {
+    let (rest, _) = keyword("switch")(input)?;
+    let (rest, value) = delimited(symbol('('), expression, char(')'))(rest)?;
+    let (mut rest, _) = symbol('{')(rest)?;
+    let mut cases = Vec::new();
+    loop {
+        if let Ok((after, _)) = symbol('}')(rest) {
+            return Ok((after, Statement::Switch(SwitchStatement { value, cases })));
+        }
+        let Ok((after, label)) = case_label(rest) else {
+            return failure(rest, "expected `case`, `default` or `}` in switch");
+        };
+        let (after, body) = statement_list(after)?;
+        cases.push(SwitchCase { label, body });
+        rest = after;
+    }
+}
+
+#[cfg(test)]
+mod switch_statement_tests {
+    use super::*;
+    use crate::ast::{Expr, Literal};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_switch_statement1() {
+        let input = r#"switch (ItemType) {
+    case 0x0:
+    case WEAPON | 1:
+        Weapon weapon;
+        break;
+    case PROTECTOR:
+        Protector protector; // falls through
+    default:
+        int32 unk;
+}
+int a;"#;
+        let (rest, result) = switch_statement(input).unwrap();
+        assert_eq!(rest, "\nint a;");
+        let Statement::Switch(SwitchStatement { value, cases }) = result else {
+            panic!("Expected Statement::Switch");
+        };
+        assert_eq!(value, Expr::Identifier("ItemType".into()));
+        let labels: Vec<_> = cases.iter().map(|case| &case.label).collect();
+        assert_eq!(labels.len(), 4);
+        assert_eq!(labels[0], &CaseLabel::Case(Expr::Literal(Literal::Int(0))));
+        assert!(matches!(labels[1], CaseLabel::Case(Expr::BinaryOp { .. })));
+        assert_eq!(
+            labels[2],
+            &CaseLabel::Case(Expr::Identifier("PROTECTOR".into()))
+        );
+        assert_eq!(labels[3], &CaseLabel::Default);
+        let lengths: Vec<_> = cases.iter().map(|case| case.body.len()).collect();
+        assert_eq!(lengths, vec![0, 2, 1, 1]);
+        assert_eq!(cases[1].body[1], Statement::Break);
+    }
+
+    #[test]
+    fn test_switch_statement_errors() {
+        assert!(matches!(
+            switch_statement("switch (a) { int b; }"),
+            Err(nom::Err::Failure(_))
+        ));
+        assert!(switch_statement("switch (a) { case 1: break;").is_err());
+        assert!(switch_statement("switchx (a) { }").is_err());
+    }
+}
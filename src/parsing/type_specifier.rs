@@ -0,0 +1,151 @@
+use nom::{combinator::verify, multi::many0};
+
+use super::statement::identifier;
+use crate::{
+    ast::TypeRef,
+    error::{failure, ParseResult},
+};
+
+/// The C keywords that combine into one built-in type, as in
+/// `unsigned long long`.
+const SPECIFIERS: [&str; 6] = ["signed", "unsigned", "char", "short", "int", "long"];
+
+/// The built-in type named by a combination of C type specifiers, such as
+/// `uint64` for `unsigned long long`, or why the combination is invalid.
+///
+/// The words may come in any order, as in C, so `int unsigned` is `uint`.
+/// A single `long` is 32 bits wide, as in 010 Editor.
+///
+/// # Example
+///
+/// ```
+/// use bt_parser::parsing::type_specifier::canonical_type;
+///
+/// assert_eq!(canonical_type(&["unsigned", "long", "long"]), Ok("uint64"));
+/// assert_eq!(canonical_type(&["signed", "char"]), Ok("char"));
+/// assert!(canonical_type(&["short", "long"]).is_err());
+/// ```
+pub fn canonical_type(words: &[&str]) -> Result<&'static str, &'static str> {
+    let count = |specifier: &str| words.iter().filter(|word| **word == specifier).count();
+    let [signed, unsigned, char, short, int, long] = SPECIFIERS.map(count);
+    if words.len() != signed + unsigned + char + short + int + long {
+        return Err("`signed`, `unsigned`, `short` and `long` only combine with C integer types");
+    }
+    if signed > 0 && unsigned > 0 {
+        return Err("`signed` and `unsigned` cannot be combined");
+    }
+    if signed > 1 || unsigned > 1 || char > 1 || short > 1 || int > 1 || long > 2 {
+        return Err("a type specifier is repeated");
+    }
+    if char + short + (long > 0) as usize > 1 {
+        return Err("`char`, `short` and `long` cannot be combined");
+    }
+    if char > 0 && int > 0 {
+        return Err("`char` cannot be combined with `int`");
+    }
+    let name = match (char > 0, short > 0, long, unsigned > 0) {
+        (true, _, _, false) => "char",
+        (true, _, _, true) => "uchar",
+        (_, true, _, false) => "short",
+        (_, true, _, true) => "ushort",
+        (_, _, 2, false) => "int64",
+        (_, _, 2, true) => "uint64",
+        (_, _, 1, false) => "long",
+        (_, _, 1, true) => "ulong",
+        (_, _, _, false) => "int",
+        (_, _, _, true) => "uint",
+    };
+    Ok(name)
+}
+
+/// Parses the type at the start of a declaration, parameter or function.
+///
+/// A run of C type specifiers such as `unsigned long long` becomes its
+/// canonical built-in type (see [`canonical_type`]), and fails if the words
+/// do not form one. Any other type is a single name, e.g. `uint32` or
+/// `PlayerGameData`.
+///
+/// # Example
+///
+/// ```
+/// use bt_parser::parsing::type_specifier::type_specifier;
+///
+/// let (rest, ty) = type_specifier("unsigned int count;").unwrap();
+/// assert_eq!(rest, " count;");
+/// assert_eq!(ty, "uint".into());
+/// ```
+pub fn type_specifier(input: &str) -> ParseResult<'_, TypeRef> {
+    let (rest, specifiers) =
+        many0(verify(identifier, |word: &str| SPECIFIERS.contains(&word)))(input)?;
+    if specifiers.is_empty() {
+        let (rest, name) = identifier(input)?;
+        return Ok((rest, name.into()));
+    }
+    match canonical_type(&specifiers) {
+        Ok(name) => Ok((rest, name.into())),
+        Err(message) => failure(input.trim_start(), message),
+    }
+}
+
+#[cfg(test)]
+mod type_specifier_tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_canonical_type() {
+        let cases: [(&[&str], &str); 12] = [
+            (&["char"], "char"),
+            (&["unsigned", "char"], "uchar"),
+            (&["short", "int"], "short"),
+            (&["unsigned", "short"], "ushort"),
+            (&["signed"], "int"),
+            (&["unsigned"], "uint"),
+            (&["int", "unsigned"], "uint"),
+            (&["long", "int"], "long"),
+            (&["unsigned", "long"], "ulong"),
+            (&["long", "long"], "int64"),
+            (&["signed", "long", "long", "int"], "int64"),
+            (&["unsigned", "long", "long"], "uint64"),
+        ];
+        for (words, expected) in cases {
+            assert_eq!(canonical_type(words), Ok(expected), "{:?}", words);
+        }
+    }
+
+    #[test]
+    fn test_canonical_type_errors() {
+        assert_eq!(
+            canonical_type(&["signed", "unsigned", "int"]),
+            Err("`signed` and `unsigned` cannot be combined")
+        );
+        assert_eq!(
+            canonical_type(&["unsigned", "unsigned", "int"]),
+            Err("a type specifier is repeated")
+        );
+        assert_eq!(
+            canonical_type(&["long", "long", "long"]),
+            Err("a type specifier is repeated")
+        );
+        assert!(canonical_type(&["short", "long"]).is_err());
+        assert!(canonical_type(&["char", "int"]).is_err());
+        assert!(canonical_type(&["unsigned", "uint32"]).is_err());
+    }
+
+    #[test]
+    fn test_type_specifier() {
+        let (rest, ty) = type_specifier(" unsigned long long value;").unwrap();
+        assert_eq!(rest, " value;");
+        assert_eq!(ty, "uint64".into());
+
+        let (rest, ty) = type_specifier("uint32 unsigned_count;").unwrap();
+        assert_eq!(rest, " unsigned_count;");
+        assert_eq!(ty, "uint32".into());
+
+        assert!(matches!(
+            type_specifier("unsigned signed x;"),
+            Err(nom::Err::Failure(_))
+        ));
+        assert!(matches!(type_specifier("{"), Err(nom::Err::Error(_))));
+    }
+}
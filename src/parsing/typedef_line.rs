@@ -1,125 +1,81 @@
+pub mod inline_declaration;
+pub mod typedef_alias;
+pub mod typedef_enum;
 pub mod typedef_member;
 pub mod typedef_members;
+pub mod typedef_union;
 
-use crate::types::nested::Nested;
 use nom::{
     branch::alt,
-    bytes::complete::{tag, take_till, take_till1, take_while},
-    character::complete::{anychar, multispace0, space0},
-    combinator::{map_res, opt, peek, recognize, value},
-    error::{context, Error, ErrorKind, ParseError, VerboseError, VerboseErrorKind},
-    multi::many1,
-    sequence::{delimited, tuple, Tuple},
-    Err, IResult, Map, Parser,
+    bytes::complete::tag,
+    combinator::{map, opt, peek},
+    error::context,
+    sequence::{delimited, preceded, tuple},
 };
 
-use super::declaration_line::special_attributes::{self, special_attributes};
-use crate::shared::take_until_unbalanced::take_until_unbalanced;
+use super::{
+    comment_line::multispace_or_comment0,
+    declaration_line::special_attributes::attribute_list,
+    parameter_list::parameter_list,
+    statement::{block, identifier},
+};
+use crate::{
+    ast::{Attribute, Parameter, Statement, TypedefStruct},
+    error::ParseResult,
+};
 
-fn parse_typedef_keyword(input: &str) -> IResult<&str, &str> {
-    tag("typedef")(input.trim_start())
+fn parse_typedef_keyword(input: &str) -> ParseResult<'_, &str> {
+    preceded(multispace_or_comment0, tag("typedef"))(input)
 }
 
-fn parse_struct_keyword(input: &str) -> IResult<&str, &str> {
-    tag("struct")(input.trim_start())
+fn parse_struct_keyword(input: &str) -> ParseResult<'_, &str> {
+    preceded(multispace_or_comment0, tag("struct"))(input)
 }
 
-fn parse_name(input: &str) -> IResult<&str, &str> {
-    take_while(|c: char| c.is_alphanumeric() || c == '_')(input)
+fn parse_name(input: &str) -> ParseResult<'_, &str> {
+    map(opt(identifier), Option::unwrap_or_default)(input)
 }
 
-type UniversalEol<'a> = IResult<&'a str, Nested, nom::error::Error<&'a str>>;
-
-fn parse_typedef_args(input: &str) -> UniversalEol {
-    let mut parser = context(
-        "argument structure",
-        tuple((
-            context("opening arg tag", tag("(")),
-            context(
-                "arguments",
-                many1(tuple((
-                    multispace0,
-                    take_while(|c: char| c.is_alphanumeric()),
-                    multispace0,
-                    take_while(|c: char| c.is_alphanumeric()),
-                    alt((tag(","), tag(")"))),
-                ))),
-            ),
-        )),
-    );
-    parser(input.trim_start()).map(|(input, values)| {
-        let nested_texts = values
-            .1
-            .into_iter()
-            .map(|r| Nested::List(vec![Nested::Text(r.1.into()), Nested::Text(r.3.into())]))
-            .collect::<Vec<Nested>>();
-        (input, Nested::List(nested_texts))
-    })
+fn parse_typedef_args(input: &str) -> ParseResult<'_, Vec<Parameter>> {
+    context("argument structure", parameter_list)(input)
 }
 
-fn parse_typedef_name(input: &str) -> UniversalEol {
-    let mut parser = context(
+fn parse_typedef_name(input: &str) -> ParseResult<'_, &str> {
+    let parser = context(
         "typedef name",
-        tuple((parse_name, multispace0, peek(tag("{")))),
+        tuple((
+            parse_name,
+            multispace_or_comment0,
+            peek(alt((tag("{"), tag("(")))),
+        )),
     );
-    parser(input.trim_start()).map(|(input, values)| (input, Nested::Text(values.0.into())))
+    preceded(multispace_or_comment0, parser)(input).map(|(input, values)| (input, values.0))
 }
 
-fn parse_typedef_up_to_bracket(input: &str) -> UniversalEol {
+/// Parses everything from the opening curly bracket of a typedef to its
+/// terminating `;`: the body, the alias and any special attributes.
+fn parse_typedef_with_alias_name(
+    input: &str,
+) -> ParseResult<'_, (Vec<Statement>, &str, Vec<Attribute>)> {
+    let (input, body) = block(input)?;
     let mut parser = context(
-        "typedef up to bracket",
-        tuple((multispace0, peek(tag("{")))),
+        "typedef alias",
+        tuple((
+            delimited(multispace_or_comment0, identifier, multispace_or_comment0),
+            opt(attribute_list),
+            multispace_or_comment0,
+            tag(";"),
+        )),
     );
-    parser(input.trim_start()).map(|(input, _)| (input, Nested::Text("".into())))
-}
-
-fn parse_optional_name<'a>(input: &str) -> IResult<&str, Option<&str>, nom::error::Error<&str>> {
-    context(
-        "optional name",
-        opt(recognize(tuple((
-            take_while(char::is_alphanumeric),
-            // take_till(|c: char| c == ' '),
-            space0::<_, nom::error::Error<_>>,
-        )))),
-    )(input)
-}
-
-fn parse_typedef_with_alias_name(input: &str) -> UniversalEol {
-    let (input, optional_name) = parse_optional_name(input)?;
-    let optional_name = optional_name.unwrap();
-
-    let _optional_space = space0::<_, nom::error::Error<_>>(input)?;
-    let (input, curly_bracket_contents) = take_until_unbalanced('{', '}')(input)?;
-    let _optional_space = space0::<_, nom::error::Error<_>>(input)?;
-
-    let (input, optional_name1) = parse_optional_name(input)?;
-    let optional_name1 = optional_name1.unwrap();
-    let _optional_space = space0::<_, nom::error::Error<_>>(input)?;
-    let (input, special_attribute) = take_until_unbalanced('<', '>')(input)?;
-    let _optional_space = space0::<_, nom::error::Error<_>>(input)?;
-    let (input, special_attribute) = special_attributes(special_attribute)?;
-    let name = if !(optional_name.trim().is_empty() || optional_name1.trim().is_empty()) {
-        Nested::List(vec![optional_name.trim().into(), optional_name1.trim().into()].into())
-    } else if optional_name.trim().is_empty() {
-        Nested::Text(optional_name1.trim().into())
-    } else if optional_name1.trim().is_empty() {
-        Nested::Text(optional_name.trim().into())
-    } else {
-        panic!("Unexpected case")
-    };
-    Ok((
-        input,
-        Nested::List(vec![
-            name,
-            Nested::Text(curly_bracket_contents.trim_end().into()),
-            special_attribute,
-        ]),
-    ))
+    parser(input).map(|(input, (alias, attributes, _, _))| {
+        (input, (body, alias, attributes.unwrap_or_default()))
+    })
 }
 
 #[cfg(test)]
 mod alias_name_tests {
     use super::*;
+    use crate::ast::{Expr, Literal};
     use pretty_assertions::assert_eq;
 
     #[test]
@@ -128,34 +84,24 @@ mod alias_name_tests {
     } PlayerGameData <size=0x1B0>;"#;
         let result = parse_typedef_with_alias_name(input);
         assert!(result.is_ok());
-        let (input, result) = result.unwrap();
+        let (input, (body, alias, attributes)) = result.unwrap();
         assert_eq!(input, "");
-        match result {
-            Nested::List(values) => {
-                assert_eq!(values.len(), 3);
-                match &values[0] {
-                    Nested::List(_) => panic!("Expected Nested::List"),
-
-                    Nested::Text(value) => assert_eq!(value, "PlayerGameData"),
-                }
-                assert_eq!(values[1], Nested::Text("".into()));
-                match &values[2] {
-                    Nested::List(values) => {
-                        assert_eq!(values.len(), 2);
-                        assert_eq!(values[0], Nested::Text("size=".into()));
-                        assert_eq!(values[1], Nested::Text("0x1B0".into()));
-                    }
-                    _ => panic!("Expected Nested::List"),
-                }
-            }
-            _ => panic!("Expected Nested::List"),
-        }
+        assert_eq!(body, vec![]);
+        assert_eq!(alias, "PlayerGameData");
+        assert_eq!(
+            attributes,
+            vec![Attribute {
+                name: "size".into(),
+                value: "0x1B0".into(),
+                expr: Expr::Literal(Literal::Int(0x1B0)),
+            }]
+        );
     }
 
     #[test]
     fn test_parse_alias_name2() {
         let input = r#"{
-    } PlayerGameData;"#;
+    } PlayerGameData"#;
         let result = parse_typedef_with_alias_name(input);
         assert!(result.is_err());
     }
@@ -167,37 +113,45 @@ mod alias_name_tests {
         let result = parse_typedef_with_alias_name(input);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_parse_alias_name4() {
+        let input = r#"{
+    } PlayerGameData;
+int a;"#;
+        let result = parse_typedef_with_alias_name(input);
+        assert_eq!(result, Ok(("\nint a;", (vec![], "PlayerGameData", vec![]))));
+    }
 }
 
-pub fn typedef_line(input: &str) -> IResult<&str, Nested> {
+pub fn typedef_line(input: &str) -> ParseResult<'_, TypedefStruct> {
     let mut parser = tuple((
         parse_typedef_keyword,
-        value((), multispace0),
+        multispace_or_comment0,
         alt((parse_struct_keyword,)), // should implement other keywords
-        value((), multispace0),
-        alt((
-            parse_typedef_with_alias_name,
-            parse_typedef_name,
-            parse_typedef_up_to_bracket,
-            parse_typedef_args,
-        )),
+        multispace_or_comment0,
+        opt(parse_typedef_name),
+        opt(parse_typedef_args),
+        parse_typedef_with_alias_name,
     ));
     parser(input).map(|(input, values)| {
-        let nested_texts = vec![
-            Nested::Text(values.0.into()),
-            // Ignore multi spaces
-            Nested::Text(values.2.into()),
-            // Ignore multi spaces
-            values.4,
-        ];
-        (input.trim_start(), Nested::List(nested_texts))
+        let (_, _, _, _, name, params, (body, alias, attributes)) = values;
+        (
+            input,
+            TypedefStruct {
+                name: name.filter(|name| !name.is_empty()).map(Into::into),
+                params: params.unwrap_or_default(),
+                body,
+                alias: alias.into(),
+                attributes,
+                doc: None,
+            },
+        )
     })
 }
 
 #[cfg(test)]
 mod typedef_args_tests {
-    use crate::types::nested::Nested;
-
     use super::*;
 
     #[test]
@@ -208,11 +162,22 @@ mod typedef_args_tests {
             result,
             Ok((
                 "",
-                Nested::List(vec![
-                    Nested::List(vec!["int".into(), "size".into()]),
-                    Nested::List(vec!["int".into(), "size2".into()]),
-                ])
-                .into()
+                vec![
+                    Parameter {
+                        ty: "int".into(),
+                        name: "size".into(),
+                        by_ref: false,
+                        is_const: false,
+                        is_array: false,
+                    },
+                    Parameter {
+                        ty: "int".into(),
+                        name: "size2".into(),
+                        by_ref: false,
+                        is_const: false,
+                        is_array: false,
+                    },
+                ]
             ))
         );
     }
@@ -220,15 +185,13 @@ mod typedef_args_tests {
 
 #[cfg(test)]
 mod typedef_name_tests {
-    use crate::types::nested::Nested;
-
     use super::*;
 
     #[test]
     fn test_parse_typedef_name1() {
         let input = r#"Foo {"#;
         let result = parse_typedef_name(input);
-        assert_eq!(result, Ok(("{", "Foo".into())));
+        assert_eq!(result, Ok(("{", "Foo")));
     }
 
     #[test]
@@ -244,10 +207,10 @@ mod typedef_name_tests {
 }
 #[cfg(test)]
 mod typedef_line_tests {
-    use crate::types::nested::Nested;
     use pretty_assertions::assert_eq;
 
     use super::*;
+    use crate::ast::{Declaration, Expr, Literal};
 
     #[test]
     fn test_parse_typedef_line1() {
@@ -257,38 +220,22 @@ mod typedef_line_tests {
         let result = typedef_line(input);
         assert!(result.is_ok());
         let (input, result) = result.unwrap();
-        assert_eq!(input, "");
-        match result {
-            Nested::List(values) => {
-                assert_eq!(values.len(), 3);
-                assert_eq!(values[0], Nested::Text("typedef".into()));
-                assert_eq!(values[1], Nested::Text("struct".into()));
-                match &values[2] {
-                    Nested::List(values) => {
-                        assert_eq!(values.len(), 3);
-                        match &values[0] {
-                            Nested::List(value) => {
-                                assert_eq!(value.len(), 2);
-                                assert_eq!(value[0], Nested::Text("Foo".into()));
-                                assert_eq!(value[1], Nested::Text("PlayerGameData".into()));
-                            },
-                            _ => panic!("Expected Nested::List"),
-                        }
-                        assert_eq!(values[1], Nested::Text("".into()));
-                        match &values[2] {
-                            Nested::List(values) => {
-                                assert_eq!(values.len(), 2);
-                                assert_eq!(values[0], Nested::Text("size=".into()));
-                                assert_eq!(values[1], Nested::Text("0x1B0".into()));
-                            }
-                            _ => panic!("Expected Nested::List"),
-                        }
-                    }
-                    _ => panic!("Expected Nested::List"),
-                }
+        assert_eq!(input, "\n");
+        assert_eq!(
+            result,
+            TypedefStruct {
+                name: Some("Foo".into()),
+                params: vec![],
+                body: vec![],
+                alias: "PlayerGameData".into(),
+                attributes: vec![Attribute {
+                    name: "size".into(),
+                    value: "0x1B0".into(),
+                    expr: Expr::Literal(Literal::Int(0x1B0)),
+                }],
+                doc: None,
             }
-            _ => panic!("Expected Nested::List"),
-        }
+        );
     }
 
     #[test]
@@ -300,38 +247,29 @@ mod typedef_line_tests {
         let result = typedef_line(input);
         assert!(result.is_ok());
         let (input, result) = result.unwrap();
-        assert_eq!(input, "");
-        match result {
-            Nested::List(values) => {
-                assert_eq!(values.len(), 3);
-                assert_eq!(values[0], Nested::Text("typedef".into()));
-                assert_eq!(values[1], Nested::Text("struct".into()));
-                match &values[2] {
-                    Nested::List(values) => {
-                        assert_eq!(values.len(), 3);
-                        assert_eq!(values[0], Nested::Text("PlayerGameData".into()));
-                        match &values[1] {
-                            Nested::Text(value) => {
-                                let byte_value = value.as_bytes();
-                                let expected_byte_value = "wchar_t  CharacterName[0x10];".as_bytes(); 
-                                assert_eq!(byte_value, expected_byte_value);
-                            },
-                            _ => panic!("Expected Nested::Text"),
-                        }
-                        match &values[2] {
-                            Nested::List(values) => {
-                                assert_eq!(values.len(), 2);
-                                assert_eq!(values[0], Nested::Text("size=".into()));
-                                assert_eq!(values[1], Nested::Text("0x1B0".into()));
-                            }
-                            _ => panic!("Expected Nested::List"),
-                        }
-                    }
-                    _ => panic!("Expected Nested::List"),
-                }
-            }
-            _ => panic!("Expected Nested::List"),
-        }
+        assert_eq!(input, "\n");
+        assert_eq!(result.name, None);
+        assert_eq!(result.alias, "PlayerGameData");
+        assert_eq!(
+            result.body,
+            vec![Statement::Declaration(Declaration {
+                ty: "wchar_t".into(),
+                name: Some("CharacterName".into()),
+                args: vec![],
+                dimensions: vec![Some(Expr::Literal(Literal::Int(0x10)))],
+                bitfield: None,
+                attributes: vec![],
+                doc: None,
+            })]
+        );
+        assert_eq!(
+            result.attributes,
+            vec![Attribute {
+                name: "size".into(),
+                value: "0x1B0".into(),
+                expr: Expr::Literal(Literal::Int(0x1B0)),
+            }]
+        );
     }
 
     #[test]
@@ -344,19 +282,73 @@ mod typedef_line_tests {
         assert_eq!(
             result,
             Ok((
-                r#"{
-  EquipInventoryDataEntry CharacterName[size];
-} EquipInventoryData;
-"#,
-                Nested::List(vec![
-                    Nested::Text("typedef".into()),
-                    Nested::Text("struct".into()),
-                    Nested::List(vec![
-                        Nested::List(vec!["int".into(), "size".into()]),
-                        Nested::List(vec!["int".into(), "size2".into()])
-                    ]),
-                ])
+                "\n",
+                TypedefStruct {
+                    name: None,
+                    params: vec![
+                        Parameter {
+                            ty: "int".into(),
+                            name: "size".into(),
+                            by_ref: false,
+                            is_const: false,
+                            is_array: false,
+                        },
+                        Parameter {
+                            ty: "int".into(),
+                            name: "size2".into(),
+                            by_ref: false,
+                            is_const: false,
+                            is_array: false,
+                        },
+                    ],
+                    body: vec![Statement::Declaration(Declaration {
+                        ty: "EquipInventoryDataEntry".into(),
+                        name: Some("CharacterName".into()),
+                        args: vec![],
+                        dimensions: vec![Some(Expr::Identifier("size".into()))],
+                        bitfield: None,
+                        attributes: vec![],
+                        doc: None,
+                    })],
+                    alias: "EquipInventoryData".into(),
+                    attributes: vec![],
+                    doc: None,
+                }
             ))
         );
     }
+
+    #[test]
+    fn test_parse_typedef_line4() {
+        let input = r#"typedef enum <uchar> { A, B } Letters;"#;
+        assert!(typedef_line(input).is_err());
+    }
+
+    #[test]
+    fn test_parse_typedef_line5() {
+        let input = r#"typedef struct Entry (const unsigned int &count, uchar data[]) {
+} ENTRY;"#;
+        let (rest, result) = typedef_line(input).unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(result.name, Some("Entry".into()));
+        assert_eq!(
+            result.params,
+            vec![
+                Parameter {
+                    ty: "uint".into(),
+                    name: "count".into(),
+                    by_ref: true,
+                    is_const: true,
+                    is_array: false,
+                },
+                Parameter {
+                    ty: "uchar".into(),
+                    name: "data".into(),
+                    by_ref: false,
+                    is_const: false,
+                    is_array: true,
+                },
+            ]
+        );
+    }
 }
@@ -0,0 +1,210 @@
+use nom::{
+    branch::alt,
+    character::complete::char,
+    combinator::{opt, peek},
+    sequence::preceded,
+};
+
+use super::typedef_enum::{enum_constants, enum_type};
+use crate::{
+    ast::{InlineDeclaration, InlineType, TypedefEnum, TypedefStruct, TypedefUnion},
+    error::{failure, ParseResult},
+    parsing::{
+        comment_line::multispace_or_comment0,
+        declaration_line::declarator_list,
+        statement::{block, identifier, keyword},
+    },
+};
+
+/// Parses a declaration that defines its type in place, such as
+/// `struct { uint16 x, y; } pos;` or `enum <ubyte> { OFF, ON } state;`.
+///
+/// A `struct`, `union` or `enum` keyword not followed by a body, as in
+/// `struct Header h;`, names an existing type and is left to another parser,
+/// as is a `union { ... };` that declares no variable. A named struct or
+/// enum such as `struct Header { ... };` may declare no variable, defining
+/// only the type.
+///
+/// # Example
+///
+/// ```
+/// use bt_parser::parsing::typedef_line::inline_declaration::inline_declaration;
+///
+/// let (_, result) = inline_declaration("struct { uint16 x, y; } pos;").unwrap();
+/// assert_eq!(result.ty.alias(), "struct@pos");
+/// assert_eq!(result.declarations[0].ty, "struct@pos".into());
+/// ```
+pub fn inline_declaration(input: &str) -> ParseResult<'_, InlineDeclaration> {
+    let (rest, kind) = alt((keyword("struct"), keyword("union"), keyword("enum")))(input)?;
+    let (rest, enum_ty) = match kind {
+        "enum" => opt(enum_type)(rest)?,
+        _ => (rest, None),
+    };
+    let (rest, name) = opt(identifier)(rest)?;
+    let (rest, _) = peek(preceded(multispace_or_comment0, char('{')))(rest)?;
+    let (rest, (body, constants)) = match kind {
+        "enum" => {
+            let (rest, _) = preceded(multispace_or_comment0, char('{'))(rest)?;
+            let (rest, constants) = enum_constants(rest)?;
+            (rest, (Vec::new(), constants))
+        }
+        _ => {
+            let (rest, body) = block(rest)?;
+            (rest, (body, Vec::new()))
+        }
+    };
+    let (rest, mut declarations) = match declarator_list(&"".into(), rest) {
+        Ok(result) => result,
+        Err(nom::Err::Error(error)) if kind == "union" => return Err(nom::Err::Error(error)),
+        Err(nom::Err::Error(_)) => match preceded(multispace_or_comment0, char(';'))(rest) {
+            Ok((rest, _)) if name.is_some() => (rest, Vec::new()),
+            _ => return failure(rest, "expected a variable name after `}`"),
+        },
+        Err(error) => return Err(error),
+    };
+    let first = declarations.first().and_then(|d| d.name.as_deref());
+    let alias = match (name, first) {
+        (Some(name), _) => name.to_string(),
+        (None, first) => format!("{}@{}", kind, first.unwrap_or_default()),
+    };
+    for declaration in &mut declarations {
+        declaration.ty = alias.as_str().into();
+    }
+    let name = name.map(String::from);
+    let ty = match kind {
+        "struct" => InlineType::Struct(TypedefStruct {
+            name,
+            params: Vec::new(),
+            body,
+            alias,
+            attributes: Vec::new(),
+            doc: None,
+        }),
+        "union" => InlineType::Union(TypedefUnion {
+            name,
+            body,
+            alias,
+            attributes: Vec::new(),
+            doc: None,
+        }),
+        _ => InlineType::Enum(TypedefEnum {
+            name,
+            ty: enum_ty,
+            constants,
+            alias,
+            attributes: Vec::new(),
+            doc: None,
+        }),
+    };
+    Ok((
+        rest,
+        InlineDeclaration {
+            ty,
+            declarations,
+            doc: None,
+        },
+    ))
+}
+
+#[cfg(test)]
+mod inline_declaration_tests {
+    use super::*;
+    use crate::ast::{Attribute, Declaration, Expr, Statement};
+    use pretty_assertions::assert_eq;
+
+    fn member(ty: &str, name: &str) -> Declaration {
+        Declaration {
+            ty: ty.into(),
+            name: Some(name.into()),
+            args: vec![],
+            dimensions: vec![],
+            bitfield: None,
+            attributes: vec![],
+            doc: None,
+        }
+    }
+
+    #[test]
+    fn test_inline_struct() {
+        let (rest, result) =
+            inline_declaration("struct { uint16 x, y; } pos, last;\nint a;").unwrap();
+        assert_eq!(rest, "\nint a;");
+        assert_eq!(
+            result,
+            InlineDeclaration {
+                ty: InlineType::Struct(TypedefStruct {
+                    name: None,
+                    params: vec![],
+                    body: vec![
+                        Statement::Declaration(member("uint16", "x")),
+                        Statement::Declaration(member("uint16", "y")),
+                    ],
+                    alias: "struct@pos".into(),
+                    attributes: vec![],
+                    doc: None,
+                }),
+                declarations: vec![member("struct@pos", "pos"), member("struct@pos", "last")],
+                doc: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_inline_enum() {
+        let (_, result) =
+            inline_declaration("enum <ubyte> { OFF, ON } state <format=hex>;").unwrap();
+        let InlineType::Enum(typedef) = &result.ty else {
+            panic!("Expected InlineType::Enum");
+        };
+        assert_eq!(typedef.ty, Some("ubyte".into()));
+        let values: Vec<_> = typedef
+            .constants
+            .iter()
+            .map(|c| (c.name.as_str(), c.value))
+            .collect();
+        assert_eq!(values, vec![("OFF", 0), ("ON", 1)]);
+        assert_eq!(
+            result.declarations,
+            vec![Declaration {
+                attributes: vec![Attribute {
+                    name: "format".into(),
+                    value: "hex".into(),
+                    expr: Expr::Identifier("hex".into()),
+                }],
+                ..member("enum@state", "state")
+            }]
+        );
+    }
+
+    #[test]
+    fn test_inline_named_union() {
+        let (_, result) = inline_declaration("union Value { int32 i; float f; } v[2];").unwrap();
+        assert_eq!(result.ty.alias(), "Value");
+        assert!(matches!(&result.ty, InlineType::Union(union) if union.body.len() == 2));
+        assert_eq!(result.declarations[0].ty, "Value".into());
+        assert_eq!(result.declarations[0].dimensions.len(), 1);
+    }
+
+    #[test]
+    fn test_inline_declaration_errors() {
+        assert!(matches!(
+            inline_declaration("struct Header h;"),
+            Err(nom::Err::Error(_))
+        ));
+        assert!(matches!(
+            inline_declaration("union { int a; };"),
+            Err(nom::Err::Error(_))
+        ));
+        let (_, result) = inline_declaration("struct H { uint32 a : 1, b : 2; };").unwrap();
+        assert_eq!(result.ty.alias(), "H");
+        assert_eq!(result.declarations, vec![]);
+        assert!(matches!(
+            inline_declaration("struct { int a; };"),
+            Err(nom::Err::Failure(_))
+        ));
+        assert!(matches!(
+            inline_declaration("struct { int a; } p"),
+            Err(nom::Err::Failure(_))
+        ));
+    }
+}
@@ -0,0 +1,115 @@
+use nom::{character::complete::char, combinator::opt, multi::many0, sequence::preceded};
+
+use crate::{
+    ast::TypedefAlias,
+    error::{failure, ParseResult},
+    parsing::{
+        comment_line::multispace_or_comment0,
+        declaration_line::{array_dimension, special_attributes::attribute_list},
+        statement::{identifier, keyword},
+        type_specifier::type_specifier,
+    },
+};
+
+/// Keywords that start a typedef with a body, parsed by the other typedef
+/// parsers.
+const TAGS: [&str; 3] = ["struct", "union", "enum"];
+
+/// Parses `typedef type alias [bounds] [<attributes>];`, which names an
+/// existing type, e.g. `typedef uint32 ItemHandle <format=hex>;` or the array
+/// `typedef char MAGIC[4];`.
+///
+/// # Example
+///
+/// ```
+/// use bt_parser::parsing::typedef_line::typedef_alias::typedef_alias;
+///
+/// let (_, result) = typedef_alias("typedef unsigned short WORD_ID <format=hex>;").unwrap();
+/// assert_eq!(result.ty, "ushort".into());
+/// assert_eq!(result.alias, "WORD_ID");
+/// assert_eq!(result.attributes[0].value, "hex");
+/// ```
+pub fn typedef_alias(input: &str) -> ParseResult<'_, TypedefAlias> {
+    let (rest, _) = keyword("typedef")(input)?;
+    let (rest, ty) = type_specifier(rest)?;
+    if TAGS.contains(&ty.name.as_str()) {
+        return Err(nom::Err::Error(nom::error_position!(
+            input,
+            nom::error::ErrorKind::Tag
+        )));
+    }
+    let Ok((rest, alias)) = identifier(rest) else {
+        return failure(rest, "expected a typedef alias name");
+    };
+    let (rest, dimensions) = many0(array_dimension)(rest)?;
+    let (rest, attributes) = opt(preceded(multispace_or_comment0, attribute_list))(rest)?;
+    let Ok((rest, _)) = preceded(multispace_or_comment0, char(';'))(rest) else {
+        return failure(rest, "expected `;` after the typedef alias");
+    };
+    Ok((
+        rest,
+        TypedefAlias {
+            ty,
+            alias: alias.into(),
+            dimensions,
+            attributes: attributes.unwrap_or_default(),
+            doc: None,
+        },
+    ))
+}
+
+#[cfg(test)]
+mod typedef_alias_tests {
+    use super::*;
+    use crate::ast::{Attribute, Expr, Literal};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_typedef_alias1() {
+        let (rest, result) =
+            typedef_alias("typedef uint32 ItemHandle <format=hex>;\nint a;").unwrap();
+        assert_eq!(rest, "\nint a;");
+        assert_eq!(
+            result,
+            TypedefAlias {
+                ty: "uint32".into(),
+                alias: "ItemHandle".into(),
+                dimensions: vec![],
+                attributes: vec![Attribute {
+                    name: "format".into(),
+                    value: "hex".into(),
+                    expr: Expr::Identifier("hex".into()),
+                }],
+                doc: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_typedef_alias2() {
+        let (rest, result) = typedef_alias("typedef char MAGIC[4];").unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(result.ty, "char".into());
+        assert_eq!(result.alias, "MAGIC");
+        assert_eq!(
+            result.dimensions,
+            vec![Some(Expr::Literal(Literal::Int(4)))]
+        );
+    }
+
+    #[test]
+    fn test_typedef_alias_errors() {
+        assert!(matches!(
+            typedef_alias("typedef struct { int a; } A;"),
+            Err(nom::Err::Error(_))
+        ));
+        assert!(matches!(
+            typedef_alias("typedef uint32;"),
+            Err(nom::Err::Failure(_))
+        ));
+        assert!(matches!(
+            typedef_alias("typedef uint32 A B;"),
+            Err(nom::Err::Failure(_))
+        ));
+    }
+}
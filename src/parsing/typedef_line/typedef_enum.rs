@@ -0,0 +1,255 @@
+use std::collections::HashMap;
+
+use nom::{
+    bytes::complete::tag,
+    character::complete::char,
+    combinator::opt,
+    error::context,
+    sequence::{preceded, tuple},
+};
+
+use super::parse_typedef_keyword;
+use crate::{
+    ast::{EnumConstant, TypeRef, TypedefEnum},
+    error::{failure, ParseResult},
+    eval::eval_constant,
+    parse_nested::constant_expression,
+    parsing::{
+        comment_line::{doc_comments, multispace_or_comment0, trailing_comment},
+        declaration_line::special_attributes::attribute_list,
+        statement::identifier,
+        type_specifier::type_specifier,
+    },
+};
+
+/// The backing type between angle brackets, e.g. `<uchar>` or
+/// `<unsigned short>`.
+pub fn enum_type(input: &str) -> ParseResult<'_, TypeRef> {
+    let (rest, _) = preceded(multispace_or_comment0, char('<'))(input)?;
+    let (rest, ty) = match type_specifier(rest) {
+        Ok(result) => result,
+        Err(nom::Err::Error(_)) => return failure(input, "expected an enum backing type"),
+        Err(error) => return Err(error),
+    };
+    match preceded(multispace_or_comment0, char('>'))(rest) {
+        Ok((rest, _)) => Ok((rest, ty)),
+        Err(_) => failure(rest, "expected `>` after the enum backing type"),
+    }
+}
+
+/// Parses the constants after the opening `{` up to and including the
+/// closing `}`, evaluating each value as it goes.
+pub fn enum_constants(input: &str) -> ParseResult<'_, Vec<EnumConstant>> {
+    let mut constants: Vec<EnumConstant> = Vec::new();
+    let mut values = HashMap::new();
+    let mut next_value = 0;
+    let mut rest = input;
+    loop {
+        let (after_docs, mut doc) = doc_comments(rest)?;
+        if let Ok((after, _)) = char::<_, ()>('}')(after_docs) {
+            return Ok((after, constants));
+        }
+        let (after, name) = identifier(after_docs)?;
+        if values.contains_key(name) {
+            return failure(after_docs, "duplicate enum constant");
+        }
+        let (after, expr) = opt(preceded(
+            preceded(multispace_or_comment0, char('=')),
+            constant_expression,
+        ))(after)?;
+        let value = match &expr {
+            Some(expr) => match eval_constant(expr, &values) {
+                Ok(value) => value,
+                Err(message) => return failure(after_docs, message),
+            },
+            None => next_value,
+        };
+        let (after, comma) = opt(preceded(multispace_or_comment0, char(',')))(after)?;
+        rest = after;
+        if let Some((after, text)) = trailing_comment(rest) {
+            doc = Some(match doc {
+                Some(doc) => format!("{}\n{}", doc, text),
+                None => text.into(),
+            });
+            rest = after;
+        }
+
+        values.insert(name.to_string(), value);
+        next_value = value.wrapping_add(1);
+        constants.push(EnumConstant {
+            name: name.into(),
+            expr,
+            value,
+            doc,
+        });
+        if comma.is_none() {
+            let (after, _) = preceded(multispace_or_comment0, char('}'))(rest)?;
+            return Ok((after, constants));
+        }
+    }
+}
+
+/// Parses `typedef enum [<type>] [name] { A, B = 5, C } alias [<attributes>];`.
+///
+/// Constants without an explicit value take the previous value plus one,
+/// starting from zero. Explicit values are evaluated as constant expressions
+/// and may refer to earlier constants; one that cannot be evaluated is a
+/// hard error.
+///
+/// # Example
+///
+/// ```
+/// use bt_parser::parsing::typedef_line::typedef_enum::typedef_enum;
+///
+/// let (_, result) = typedef_enum("typedef enum <uchar> { A, B = 5, C } Kind;").unwrap();
+/// assert_eq!(result.ty, Some("uchar".into()));
+/// assert_eq!(result.alias, "Kind");
+/// let values: Vec<_> = result.constants.iter().map(|c| (c.name.as_str(), c.value)).collect();
+/// assert_eq!(values, vec![("A", 0), ("B", 5), ("C", 6)]);
+/// ```
+pub fn typedef_enum(input: &str) -> ParseResult<'_, TypedefEnum> {
+    let (input, _) = context(
+        "typedef enum",
+        tuple((
+            parse_typedef_keyword,
+            preceded(multispace_or_comment0, tag("enum")),
+        )),
+    )(input)?;
+    let (input, ty) = opt(enum_type)(input)?;
+    let (input, name) = opt(identifier)(input)?;
+    let (input, _) = preceded(multispace_or_comment0, char('{'))(input)?;
+    let (input, constants) = enum_constants(input)?;
+    let (input, alias) = identifier(input)?;
+    let (input, attributes) = opt(preceded(multispace_or_comment0, attribute_list))(input)?;
+    let (input, _) = preceded(multispace_or_comment0, char(';'))(input)?;
+    Ok((
+        input,
+        TypedefEnum {
+            name: name.map(Into::into),
+            ty,
+            constants,
+            alias: alias.into(),
+            attributes: attributes.unwrap_or_default(),
+            doc: None,
+        },
+    ))
+}
+
+#[cfg(test)]
+mod typedef_enum_tests {
+    use super::*;
+    use crate::ast::{Attribute, Expr, Literal};
+    use pretty_assertions::assert_eq;
+
+    fn constant(name: &str, value: i64, doc: Option<&str>) -> EnumConstant {
+        EnumConstant {
+            name: name.into(),
+            expr: None,
+            value,
+            doc: doc.map(Into::into),
+        }
+    }
+
+    #[test]
+    fn test_typedef_enum1() {
+        let input = r#"typedef enum <uint32> ItemCategory {
+    // Weapons and shields
+    WEAPON = 0x00000000,
+    PROTECTOR = 0x10000000, // armor
+    ACCESSORY = 0x20000000,
+    GOODS = 0x40000000,
+    GEM = GOODS | 0x80000000, /* ashes of war */
+    NONE // unset
+} ITEM_CATEGORY <format=hex>;
+int a;"#;
+        let (rest, result) = typedef_enum(input).unwrap();
+        assert_eq!(rest, "\nint a;");
+        assert_eq!(result.name, Some("ItemCategory".into()));
+        assert_eq!(result.ty, Some("uint32".into()));
+        assert_eq!(result.alias, "ITEM_CATEGORY");
+        assert_eq!(
+            result.attributes,
+            vec![Attribute {
+                name: "format".into(),
+                value: "hex".into(),
+                expr: Expr::Identifier("hex".into()),
+            }]
+        );
+        let constants: Vec<EnumConstant> = result
+            .constants
+            .into_iter()
+            .map(|constant| EnumConstant {
+                expr: None,
+                ..constant
+            })
+            .collect();
+        assert_eq!(
+            constants,
+            vec![
+                constant("WEAPON", 0, Some("Weapons and shields")),
+                constant("PROTECTOR", 0x10000000, Some("armor")),
+                constant("ACCESSORY", 0x20000000, None),
+                constant("GOODS", 0x40000000, None),
+                constant("GEM", 0xC0000000, Some("ashes of war")),
+                constant("NONE", 0xC0000001, Some("unset")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_typedef_enum2() {
+        let (rest, result) = typedef_enum("typedef enum { A, B = -2, C, } E;").unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(result.name, None);
+        assert_eq!(result.ty, None);
+        assert_eq!(
+            result.constants,
+            vec![
+                constant("A", 0, None),
+                EnumConstant {
+                    expr: Some(Expr::UnaryOp {
+                        op: crate::ast::Expression::Subtract,
+                        operand: Box::new(Expr::Literal(Literal::Int(2))),
+                    }),
+                    ..constant("B", -2, None)
+                },
+                constant("C", -1, None),
+            ]
+        );
+
+        let (_, result) = typedef_enum("typedef enum <unsigned short> { X } F;").unwrap();
+        assert_eq!(result.ty, Some("ushort".into()));
+    }
+
+    #[test]
+    fn test_typedef_enum_comment_before_comma() {
+        let (rest, result) =
+            typedef_enum("typedef enum { A /* x */ , B = 4 /* y */ , C } E;").unwrap();
+        assert_eq!(rest, "");
+        let values: Vec<_> = result
+            .constants
+            .iter()
+            .map(|c| (c.name.as_str(), c.value))
+            .collect();
+        assert_eq!(values, vec![("A", 0), ("B", 4), ("C", 5)]);
+    }
+
+    #[test]
+    fn test_typedef_enum_errors() {
+        assert!(matches!(
+            typedef_enum("typedef enum { A = B } E;"),
+            Err(nom::Err::Failure(_))
+        ));
+        assert!(matches!(
+            typedef_enum("typedef enum { A, A } E;"),
+            Err(nom::Err::Failure(_))
+        ));
+        assert!(matches!(
+            typedef_enum("typedef enum <unsigned char short> { A } E;"),
+            Err(nom::Err::Failure(_))
+        ));
+        assert!(typedef_enum("typedef enum { A B } E;").is_err());
+        assert!(typedef_enum("typedef enum { A } ;").is_err());
+        assert!(typedef_enum("typedef struct { int a; } E;").is_err());
+    }
+}
@@ -1,6 +1,8 @@
-use nom::{bytes::complete::tag, IResult};
+use nom::bytes::complete::tag;
 
-use crate::types::nested::Nested;
+use crate::error::ParseResult;
+
+use crate::{ast::TypeRef, parsing::comment_line::multispace_or_comment0};
 
 #[derive(Debug)]
 enum TypedefMember {
@@ -36,7 +38,7 @@ enum TypedefMember {
     String,
     WCharT,
     WString,
-    GUID,
+    Guid,
     Opcode,
     Struct,
     Unsigned,
@@ -78,7 +80,7 @@ impl TypedefMember {
             TypedefMember::String,
             TypedefMember::WCharT,
             TypedefMember::WString,
-            TypedefMember::GUID,
+            TypedefMember::Guid,
             TypedefMember::Opcode,
             TypedefMember::Struct,
             TypedefMember::Unsigned,
@@ -123,7 +125,7 @@ impl std::fmt::Display for TypedefMember {
             TypedefMember::String => write!(f, "string"),
             TypedefMember::WCharT => write!(f, "wchar_t"),
             TypedefMember::WString => write!(f, "wstring"),
-            TypedefMember::GUID => write!(f, "guid"),
+            TypedefMember::Guid => write!(f, "guid"),
             TypedefMember::Opcode => write!(f, "opcode"),
             TypedefMember::Struct => write!(f, "struct"),
             TypedefMember::Unsigned => write!(f, "unsigned"),
@@ -132,7 +134,7 @@ impl std::fmt::Display for TypedefMember {
     }
 }
 
-fn parse_typedef_member(input: &str) -> IResult<&str, &str> {
+fn parse_typedef_member(input: &str) -> ParseResult<'_, &str> {
     for member in TypedefMember::iterator() {
         let result = tag(member.to_string().as_str())(input);
         if result.is_ok() {
@@ -140,15 +142,23 @@ fn parse_typedef_member(input: &str) -> IResult<&str, &str> {
         }
     }
     // Return an error if none of the members match
-    return Err(nom::Err::Error(nom::error_position!(
+    Err(nom::Err::Error(nom::error_position!(
         input,
         nom::error::ErrorKind::Fail
-    )));
+    )))
+}
+
+/// Whether `name` is exactly one of the built-in type keywords, e.g. `uint32`.
+pub fn is_typedef_member(name: &str) -> bool {
+    TypedefMember::iterator().any(|member| member.to_string() == name)
 }
 
-pub fn typedef_member(input: &str) -> IResult<&str, Nested> {
+pub fn typedef_member(input: &str) -> ParseResult<'_, TypeRef> {
     match parse_typedef_member(input) {
-        Ok((rest, member)) => Ok((rest.trim_start(), Nested::Text(member.to_string()))),
+        Ok((rest, member)) => {
+            let (rest, _) = multispace_or_comment0(rest)?;
+            Ok((rest, member.into()))
+        }
         Err(_) => Err(nom::Err::Error(nom::error_position!(
             input,
             nom::error::ErrorKind::Fail
@@ -180,6 +190,14 @@ mod typedef_member_tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_is_typedef_member() {
+        assert!(is_typedef_member("uint32"));
+        assert!(is_typedef_member("wchar_t"));
+        assert!(!is_typedef_member("uint3"));
+        assert!(!is_typedef_member("ItemID"));
+    }
+
     #[test]
     fn test_typedef_member3() {
         // Test that a valid member can be parsed
@@ -190,4 +208,4 @@ mod typedef_member_tests {
         assert_eq!(rest, "int myInt;");
         assert_eq!(member, "unsigned".into());
     }
-}
\ No newline at end of file
+}
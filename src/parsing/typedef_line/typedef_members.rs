@@ -1,19 +1,15 @@
 use nom::{
-    bytes::{
-        complete::tag,
-        streaming::take_until,
-    },
+    bytes::{complete::tag, streaming::take_until},
     character::complete::char,
     combinator::{not, peek, recognize},
     sequence::{preceded, tuple},
-    IResult,
 };
 
-use crate::types::nested::Nested;
+use crate::error::ParseResult;
 
-use super::typedef_member::typedef_member;
+use crate::{ast::Declaration, parsing::type_specifier::type_specifier};
 
-fn parse_typedef_to_terminator(input: &str) -> IResult<&str, &str> {
+fn parse_typedef_to_terminator(input: &str) -> ParseResult<'_, &str> {
     let until_semicolon = take_until(";");
     let not_open_brace = not(peek(char('{')));
 
@@ -23,17 +19,17 @@ fn parse_typedef_to_terminator(input: &str) -> IResult<&str, &str> {
     parse_logic(input).and_then(|(next_input, result)| {
         // Check if the result contains '{', which should not happen
         if result.contains('{') {
-          Err(nom::Err::Error(nom::error_position!(
-            input,
-            nom::error::ErrorKind::Fail
-        )))
+            Err(nom::Err::Error(nom::error_position!(
+                input,
+                nom::error::ErrorKind::Fail
+            )))
         } else {
             Ok((next_input, result))
         }
     })
 }
 
-fn parse_statement_terminator(input: &str) -> IResult<&str, &str> {
+fn parse_statement_terminator(input: &str) -> ParseResult<'_, &str> {
     peek(tag(";"))(input)
 }
 
@@ -44,32 +40,33 @@ fn trim_start_terminator(input: &str) -> &str {
     }
 }
 
-fn parse_typedef_to_end(input: &str) -> IResult<&str, &str> {
+fn parse_typedef_to_end(input: &str) -> ParseResult<'_, &str> {
     recognize(tuple((
         parse_typedef_to_terminator,
         parse_statement_terminator,
     )))(input)
     .map(|(input, value)| {
         let rest_without_terminator = trim_start_terminator(input);
-        return (rest_without_terminator, value.trim_end());
+        (rest_without_terminator, value.trim_end())
     })
 }
 
-pub fn typedef_members(input: &str) -> IResult<&str, Nested> {
-    let mut members = Vec::new();
-    let mut rest = input;
-    loop {
-        let result = typedef_member(rest);
-        if result.is_err() {
-            break;
-        }
-        let (new_rest, member) = result.unwrap();
-        members.push(member);
-        rest = new_rest;
-    }
+pub fn typedef_members(input: &str) -> ParseResult<'_, Declaration> {
+    let (rest, ty) = type_specifier(input)?;
     match parse_typedef_to_end(rest) {
-        Ok((rest, name)) => Ok((rest, Nested::List(vec![name.into(), Nested::List(members)]))),
-        Err(_) => Err(nom::Err::Error(nom::error_position!(
+        Ok((rest, name)) if !name.trim().is_empty() => Ok((
+            rest,
+            Declaration {
+                ty,
+                name: Some(name.trim().into()),
+                args: vec![],
+                dimensions: vec![],
+                bitfield: None,
+                attributes: vec![],
+                doc: None,
+            },
+        )),
+        _ => Err(nom::Err::Error(nom::error_position!(
             input,
             nom::error::ErrorKind::Fail
         ))),
@@ -89,35 +86,19 @@ mod typedef_members_tests {
         assert!(result.is_ok());
         let (rest, members) = result.unwrap();
         assert_eq!(rest, "");
-        assert_eq!(
-            members,
-            Nested::List(vec![
-                "myInt".into(),
-                Nested::List(vec!["unsigned".into(), "int".into()])
-            ])
-        );
+        assert_eq!(members.name.as_deref(), Some("myInt"));
+        assert_eq!(members.ty, "uint".into());
     }
 
     #[test]
     fn test_typedef_members2() {
         let input = "unsigned unsigned unsigned unsigned int myInt;";
         let result = typedef_members(input);
-        assert!(result.is_ok());
-        let (rest, members) = result.unwrap();
+        assert!(matches!(result, Err(nom::Err::Failure(_))));
+
+        let (rest, members) = typedef_members("unsigned long long myInt;").unwrap();
         assert_eq!(rest, "");
-        assert_eq!(
-            members,
-            Nested::List(vec![
-                "myInt".into(),
-                Nested::List(vec![
-                    "unsigned".into(),
-                    "unsigned".into(),
-                    "unsigned".into(),
-                    "unsigned".into(),
-                    "int".into()
-                ])
-            ])
-        );
+        assert_eq!(members.ty, "uint64".into());
     }
 
     #[test]
@@ -127,13 +108,8 @@ mod typedef_members_tests {
         assert!(result.is_ok());
         let (rest, members) = result.unwrap();
         assert_eq!(rest, " unsigned int myInt2;");
-        assert_eq!(
-            members,
-            Nested::List(vec![
-                "myInt".into(),
-                Nested::List(vec!["unsigned".into(), "int".into()])
-            ])
-        );
+        assert_eq!(members.name.as_deref(), Some("myInt"));
+        assert_eq!(members.ty, "uint".into());
     }
 
     #[test]
@@ -149,4 +125,11 @@ mod typedef_members_tests {
         let result = typedef_members(input);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_typedef_member5() {
+        let input = "myInt;";
+        let result = typedef_members(input);
+        assert!(result.is_err());
+    }
 }
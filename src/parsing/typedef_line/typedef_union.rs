@@ -0,0 +1,177 @@
+use nom::{
+    character::complete::char,
+    combinator::opt,
+    error::context,
+    sequence::{preceded, tuple},
+};
+
+use super::{parse_typedef_keyword, parse_typedef_name, parse_typedef_with_alias_name};
+use crate::{
+    ast::{TypedefUnion, UnionDeclaration},
+    error::ParseResult,
+    parsing::{
+        comment_line::multispace_or_comment0,
+        declaration_line::special_attributes::attribute_list,
+        statement::{block, identifier, keyword},
+    },
+};
+
+fn parse_union_keyword(input: &str) -> ParseResult<'_, &str> {
+    keyword("union")(input)
+}
+
+/// Parses `typedef union [name] { body } alias [<attributes>];`.
+///
+/// # Example
+///
+/// ```
+/// use bt_parser::parsing::typedef_line::typedef_union::typedef_union;
+///
+/// let (_, result) = typedef_union("typedef union { uint32 raw; float f; } Value;").unwrap();
+/// assert_eq!(result.body.len(), 2);
+/// assert_eq!(result.alias, "Value");
+/// ```
+pub fn typedef_union(input: &str) -> ParseResult<'_, TypedefUnion> {
+    let (input, (_, _, name, (body, alias, attributes))) = context(
+        "typedef union",
+        tuple((
+            parse_typedef_keyword,
+            parse_union_keyword,
+            opt(parse_typedef_name),
+            parse_typedef_with_alias_name,
+        )),
+    )(input)?;
+    Ok((
+        input,
+        TypedefUnion {
+            name: name.filter(|name| !name.is_empty()).map(Into::into),
+            body,
+            alias: alias.into(),
+            attributes,
+            doc: None,
+        },
+    ))
+}
+
+/// Parses an inline `union [name] { body } [variable] [<attributes>];`.
+///
+/// # Example
+///
+/// ```
+/// use bt_parser::parsing::typedef_line::typedef_union::union_declaration;
+///
+/// let (_, result) = union_declaration("union { int32 i; float f; } u;").unwrap();
+/// assert_eq!(result.name, None);
+/// assert_eq!(result.variable.as_deref(), Some("u"));
+/// ```
+pub fn union_declaration(input: &str) -> ParseResult<'_, UnionDeclaration> {
+    let (input, _) = parse_union_keyword(input)?;
+    let (input, name) = opt(identifier)(input)?;
+    let (input, body) = block(input)?;
+    let (input, variable) = opt(identifier)(input)?;
+    let (input, attributes) = opt(preceded(multispace_or_comment0, attribute_list))(input)?;
+    let (input, _) = preceded(multispace_or_comment0, char(';'))(input)?;
+    Ok((
+        input,
+        UnionDeclaration {
+            name: name.map(Into::into),
+            body,
+            variable: variable.map(Into::into),
+            attributes: attributes.unwrap_or_default(),
+            doc: None,
+        },
+    ))
+}
+
+#[cfg(test)]
+mod typedef_union_tests {
+    use super::*;
+    use crate::ast::{Attribute, Declaration, Expr, Literal, Statement};
+    use pretty_assertions::assert_eq;
+
+    fn member(ty: &str, name: &str) -> Declaration {
+        Declaration {
+            ty: ty.into(),
+            name: Some(name.into()),
+            args: vec![],
+            dimensions: vec![],
+            bitfield: None,
+            attributes: vec![],
+            doc: None,
+        }
+    }
+
+    fn declaration(ty: &str, name: &str) -> Statement {
+        Statement::Declaration(member(ty, name))
+    }
+
+    #[test]
+    fn test_typedef_union1() {
+        let input = r#"typedef union Value {
+    uint32 raw;
+    float f;
+} VALUE <read=ReadValue>;
+int a;"#;
+        let (rest, result) = typedef_union(input).unwrap();
+        assert_eq!(rest, "\nint a;");
+        assert_eq!(
+            result,
+            TypedefUnion {
+                name: Some("Value".into()),
+                body: vec![declaration("uint32", "raw"), declaration("float", "f")],
+                alias: "VALUE".into(),
+                attributes: vec![Attribute {
+                    name: "read".into(),
+                    value: "ReadValue".into(),
+                    expr: Expr::Identifier("ReadValue".into()),
+                }],
+                doc: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_typedef_union2() {
+        assert!(typedef_union("typedef struct { int a; } S;").is_err());
+        assert!(typedef_union("typedef unionx { int a; } S;").is_err());
+        assert!(typedef_union("typedef union { int a; };").is_err());
+    }
+
+    #[test]
+    fn test_union_declaration1() {
+        let (rest, result) =
+            union_declaration("union Flags { uint32 all; ubyte b[4]; } flags <format=hex>;")
+                .unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(
+            result,
+            UnionDeclaration {
+                name: Some("Flags".into()),
+                body: vec![
+                    declaration("uint32", "all"),
+                    Statement::Declaration(Declaration {
+                        args: vec![],
+                        dimensions: vec![Some(Expr::Literal(Literal::Int(4)))],
+                        ..member("ubyte", "b")
+                    }),
+                ],
+                variable: Some("flags".into()),
+                attributes: vec![Attribute {
+                    name: "format".into(),
+                    value: "hex".into(),
+                    expr: Expr::Identifier("hex".into()),
+                }],
+                doc: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_union_declaration2() {
+        let (rest, result) = union_declaration("union { int a; float b; };").unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(result.name, None);
+        assert_eq!(result.variable, None);
+        assert!(union_declaration("union u;").is_err());
+    }
+}
@@ -0,0 +1,182 @@
+use nom::{
+    branch::alt,
+    multi::{many0, many1},
+};
+
+use super::{
+    declaration_line::array_dimension,
+    statement::{identifier, keyword, symbol},
+    type_specifier::type_specifier,
+};
+use crate::{
+    ast::{Declarator, Storage, Variable},
+    error::{failure, ParseResult},
+    parse_nested::assignment_expression,
+};
+
+/// The array bounds and initializer following a declarator's name.
+fn declarator_tail<'a>(
+    storage: Storage,
+    name: &str,
+    input: &'a str,
+) -> ParseResult<'a, Declarator> {
+    let (rest, dimensions) = many0(array_dimension)(input)?;
+    let (rest, init) = match symbol('=')(rest) {
+        Ok((rest, _)) => match assignment_expression(rest) {
+            Ok((rest, value)) => (rest, Some(value)),
+            Err(nom::Err::Error(_)) => return failure(rest, "expected an initializer after `=`"),
+            Err(error) => return Err(error),
+        },
+        Err(_) if storage == Storage::Const => {
+            return failure(rest, "a `const` variable must be initialized")
+        }
+        Err(_) => (rest, None),
+    };
+    Ok((
+        rest,
+        Declarator {
+            name: name.into(),
+            dimensions,
+            init,
+        },
+    ))
+}
+
+/// Parses a `local` or `const` variable declaration such as
+/// `local int a = 1, b, c = a + 2;`, which declares one [`Declarator`] per
+/// name. These variables live in the template rather than the file.
+///
+/// A `const` must be initialized.
+///
+/// # Example
+///
+/// ```
+/// use bt_parser::ast::Storage;
+/// use bt_parser::parsing::variable_line::variable_line;
+///
+/// let (_, result) = variable_line("const uint MAGIC = 0x4D534742;").unwrap();
+/// assert_eq!(result.storage, Storage::Const);
+/// assert_eq!(result.ty, "uint".into());
+/// assert_eq!(result.declarators[0].name, "MAGIC");
+/// ```
+pub fn variable_line(input: &str) -> ParseResult<'_, Variable> {
+    let (rest, qualifiers) = many1(alt((keyword("local"), keyword("const"))))(input)?;
+    let storage = match qualifiers.contains(&"const") {
+        true => Storage::Const,
+        false => Storage::Local,
+    };
+    let (rest, ty) = match type_specifier(rest) {
+        Ok(result) => result,
+        Err(nom::Err::Error(_)) => return failure(rest, "expected a type and name"),
+        Err(error) => return Err(error),
+    };
+    let Ok((rest, name)) = identifier(rest) else {
+        return failure(rest, "expected a type and name");
+    };
+    let (mut rest, first) = declarator_tail(storage, name, rest)?;
+    let mut declarators = vec![first];
+    while let Ok((after, _)) = symbol(',')(rest) {
+        let Ok((after, name)) = identifier(after) else {
+            return failure(after, "expected a variable name after `,`");
+        };
+        let (after, declarator) = declarator_tail(storage, name, after)?;
+        declarators.push(declarator);
+        rest = after;
+    }
+    let Ok((rest, _)) = symbol(';')(rest) else {
+        return failure(rest, "expected `,` or `;` after the variable");
+    };
+    Ok((
+        rest,
+        Variable {
+            storage,
+            ty,
+            declarators,
+            doc: None,
+        },
+    ))
+}
+
+#[cfg(test)]
+mod variable_line_tests {
+    use super::*;
+    use crate::ast::{Expr, Expression, Literal};
+    use pretty_assertions::assert_eq;
+
+    fn declarator(name: &str, init: Option<Expr>) -> Declarator {
+        Declarator {
+            name: name.into(),
+            dimensions: vec![],
+            init,
+        }
+    }
+
+    #[test]
+    fn test_variable_line1() {
+        let (rest, result) = variable_line("local int a = 1, b, c = a + 2;\nint d;").unwrap();
+        assert_eq!(rest, "\nint d;");
+        assert_eq!(
+            result,
+            Variable {
+                storage: Storage::Local,
+                ty: "int".into(),
+                declarators: vec![
+                    declarator("a", Some(Expr::Literal(Literal::Int(1)))),
+                    declarator("b", None),
+                    declarator(
+                        "c",
+                        Some(Expr::BinaryOp {
+                            left: Box::new(Expr::Identifier("a".into())),
+                            op: Expression::Add,
+                            right: Box::new(Expr::Literal(Literal::Int(2))),
+                        })
+                    ),
+                ],
+                doc: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_variable_line2() {
+        let (_, result) = variable_line("local string name;").unwrap();
+        assert_eq!(result.ty, "string".into());
+        assert_eq!(result.declarators, vec![declarator("name", None)]);
+
+        let (_, result) = variable_line("local unsigned int buf[4], n;").unwrap();
+        assert_eq!(result.storage, Storage::Local);
+        assert_eq!(result.ty, "uint".into());
+        assert_eq!(
+            result.declarators[0].dimensions,
+            vec![Some(Expr::Literal(Literal::Int(4)))]
+        );
+    }
+
+    #[test]
+    fn test_variable_line_errors() {
+        assert!(matches!(
+            variable_line("int a = 1;"),
+            Err(nom::Err::Error(_))
+        ));
+        assert!(matches!(
+            variable_line("localx int a;"),
+            Err(nom::Err::Error(_))
+        ));
+        assert!(matches!(
+            variable_line("local int;"),
+            Err(nom::Err::Failure(_))
+        ));
+        assert!(matches!(
+            variable_line("local int a = ;"),
+            Err(nom::Err::Failure(_))
+        ));
+        assert!(matches!(
+            variable_line("local int a = 1 b;"),
+            Err(nom::Err::Failure(_))
+        ));
+        assert!(matches!(
+            variable_line("local const uint A = 1, B;"),
+            Err(nom::Err::Failure(_))
+        ));
+    }
+}
@@ -0,0 +1,261 @@
+use std::{
+    cmp::Reverse,
+    fmt::{self, Display, Formatter},
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    error::ParseError,
+    metadata::{parse_metadata, TemplateMetadata},
+};
+
+/// A template known to a [`TemplateRegistry`], identified by its path.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegisteredTemplate {
+    pub path: PathBuf,
+    pub metadata: TemplateMetadata,
+}
+
+impl RegisteredTemplate {
+    /// How well the template matches a file, or `None` if it does not.
+    ///
+    /// A template must declare a `File Mask` or `ID Bytes`, and every one it
+    /// declares must match. Matches rank by the most specific matching
+    /// signature, then by the most specific matching mask.
+    fn score(&self, file_name: &str, leading_bytes: &[u8]) -> Option<(usize, usize)> {
+        let metadata = &self.metadata;
+        if metadata.file_masks.is_empty() && metadata.id_bytes.is_empty() {
+            return None;
+        }
+        let mask = if metadata.file_masks.is_empty() {
+            0
+        } else {
+            metadata
+                .file_masks
+                .iter()
+                .filter(|mask| mask.matches(file_name))
+                .map(|mask| mask.specificity())
+                .max()?
+        };
+        let signature = if metadata.id_bytes.is_empty() {
+            0
+        } else {
+            metadata
+                .id_bytes
+                .iter()
+                .filter(|pattern| pattern.matches(leading_bytes))
+                .map(|pattern| pattern.specificity())
+                .max()?
+        };
+        Some((signature, mask))
+    }
+}
+
+/// An error raised while loading templates into a [`TemplateRegistry`].
+#[derive(Debug)]
+pub enum RegistryError {
+    Io { path: PathBuf, error: io::Error },
+    Parse { path: PathBuf, error: ParseError },
+}
+
+impl Display for RegistryError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            RegistryError::Io { path, error } => write!(f, "{}: {}", path.display(), error),
+            RegistryError::Parse { path, error } => write!(f, "{}: {}", path.display(), error),
+        }
+    }
+}
+
+impl std::error::Error for RegistryError {}
+
+/// A set of templates that can be matched against binary files by their
+/// header `File Mask` and `ID Bytes`, the way 010 Editor picks a template to
+/// run when a file is opened.
+///
+/// # Example
+///
+/// ```
+/// use std::path::Path;
+/// use bt_parser::{metadata::parse_metadata, registry::TemplateRegistry};
+///
+/// let mut registry = TemplateRegistry::new();
+/// let header = "// File Mask: *.zip\n// ID Bytes: 50 4B 03 04\n";
+/// registry.register("ZIP.bt", parse_metadata(header).unwrap().unwrap());
+///
+/// let selected = registry.select(Path::new("data/archive.zip"), b"PK\x03\x04");
+/// assert_eq!(selected[0].path, Path::new("ZIP.bt"));
+/// assert!(registry.select(Path::new("archive.zip"), b"MZ").is_empty());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TemplateRegistry {
+    templates: Vec<RegisteredTemplate>,
+}
+
+impl TemplateRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers every `.bt` file directly inside `dir` that has a header
+    /// banner. Only the header of each file is parsed.
+    ///
+    /// A file that cannot be read or whose header cannot be decoded is
+    /// skipped, and its error is returned alongside the registry. Only a
+    /// directory that cannot be listed fails outright.
+    pub fn load_dir(dir: impl AsRef<Path>) -> Result<(Self, Vec<RegistryError>), RegistryError> {
+        let dir = dir.as_ref();
+        let io_error = |path: &Path| {
+            let path = path.to_path_buf();
+            move |error| RegistryError::Io { path, error }
+        };
+        let mut paths = fs::read_dir(dir)
+            .map_err(io_error(dir))?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(io_error(dir))?;
+        paths.retain(|path| {
+            path.is_file()
+                && path
+                    .extension()
+                    .is_some_and(|extension| extension.eq_ignore_ascii_case("bt"))
+        });
+        paths.sort();
+
+        let mut registry = Self::new();
+        let mut errors = Vec::new();
+        for path in paths {
+            // Templates are not always UTF-8; the header is plain ASCII.
+            let bytes = match fs::read(&path) {
+                Ok(bytes) => bytes,
+                Err(error) => {
+                    errors.push(RegistryError::Io { path, error });
+                    continue;
+                }
+            };
+            let source = String::from_utf8_lossy(&bytes);
+            match parse_metadata(&source) {
+                Ok(Some(metadata)) => registry.register(path, metadata),
+                Ok(None) => {}
+                Err(error) => errors.push(RegistryError::Parse { path, error }),
+            }
+        }
+        Ok((registry, errors))
+    }
+
+    pub fn register(&mut self, path: impl Into<PathBuf>, metadata: TemplateMetadata) {
+        self.templates.push(RegisteredTemplate {
+            path: path.into(),
+            metadata,
+        });
+    }
+
+    pub fn templates(&self) -> &[RegisteredTemplate] {
+        &self.templates
+    }
+
+    /// How many leading bytes of a file [`select`](Self::select) needs to
+    /// check every registered signature.
+    pub fn signature_len(&self) -> usize {
+        self.templates
+            .iter()
+            .flat_map(|template| &template.metadata.id_bytes)
+            .map(|pattern| pattern.end())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// The templates matching the file at `path` whose contents start with
+    /// `leading_bytes`, best match first. Ties keep registration order.
+    pub fn select(&self, path: &Path, leading_bytes: &[u8]) -> Vec<&RegisteredTemplate> {
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy())
+            .unwrap_or_default();
+        let mut matches: Vec<_> = self
+            .templates
+            .iter()
+            .filter_map(|template| Some((template.score(&file_name, leading_bytes)?, template)))
+            .collect();
+        matches.sort_by_key(|(score, _)| Reverse(*score));
+        matches.into_iter().map(|(_, template)| template).collect()
+    }
+}
+
+#[cfg(test)]
+mod registry_tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn registry(headers: &[(&str, &str)]) -> TemplateRegistry {
+        let mut registry = TemplateRegistry::new();
+        for (path, header) in headers {
+            registry.register(*path, parse_metadata(header).unwrap().unwrap());
+        }
+        registry
+    }
+
+    fn selected(registry: &TemplateRegistry, path: &str, bytes: &[u8]) -> Vec<String> {
+        registry
+            .select(Path::new(path), bytes)
+            .into_iter()
+            .map(|template| template.path.display().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_select_ranks_by_specificity() {
+        let registry = registry(&[
+            ("Any.bt", "// File Mask: *\n"),
+            ("SL2.bt", "// File Mask: *.sl2\n// ID Bytes: 42 4E 44 34\n"),
+            (
+                "ER.bt",
+                "// File Mask: ER????.sl2\n// ID Bytes: 42 4E 44 34\n",
+            ),
+            ("Magic.bt", "// ID Bytes: 42 4E ?? ??\n"),
+        ]);
+        assert_eq!(
+            selected(&registry, "saves/ER0000.sl2", b"BND4\x00"),
+            vec!["ER.bt", "SL2.bt", "Magic.bt", "Any.bt"]
+        );
+        assert_eq!(selected(&registry, "other.sl2", b"RIFF"), vec!["Any.bt"]);
+    }
+
+    #[test]
+    fn test_select_requires_mask_or_signature() {
+        let registry = registry(&[("Untyped.bt", "// File: Untyped.bt\n")]);
+        assert!(selected(&registry, "a.bin", b"").is_empty());
+    }
+
+    #[test]
+    fn test_signature_len() {
+        let registry = registry(&[
+            ("A.bt", "// ID Bytes: 50 4B\n"),
+            ("B.bt", "// ID Bytes: [+4] 66 74 79 70\n"),
+        ]);
+        assert_eq!(registry.signature_len(), 8);
+        assert_eq!(TemplateRegistry::new().signature_len(), 0);
+    }
+
+    #[test]
+    fn test_load_dir() {
+        let dir = std::env::temp_dir().join(format!("bt_parser_registry_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("ZIP.bt"), "// File Mask: *.zip\nint a;\n").unwrap();
+        fs::write(dir.join("NoHeader.bt"), "int a;\n").unwrap();
+        fs::write(dir.join("notes.txt"), "// File Mask: *.zip\n").unwrap();
+        fs::write(dir.join("Bad.bt"), "// ID Bytes: XY\nint a;\n").unwrap();
+
+        let result = TemplateRegistry::load_dir(&dir);
+        fs::remove_dir_all(&dir).unwrap();
+
+        let (registry, errors) = result.unwrap();
+        assert_eq!(registry.templates().len(), 1);
+        assert_eq!(registry.templates()[0].path, dir.join("ZIP.bt"));
+        assert_eq!(errors.len(), 1);
+        assert!(
+            matches!(&errors[0], RegistryError::Parse { path, .. } if *path == dir.join("Bad.bt"))
+        );
+    }
+}
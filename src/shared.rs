@@ -1 +1,2 @@
-pub mod take_until_unbalanced;
\ No newline at end of file
+pub mod glob;
+pub mod take_until_unbalanced;
@@ -0,0 +1,88 @@
+/// A `File Mask` pattern such as `*.sl2`, matched case-insensitively against
+/// file names. `*` matches any run of characters and `?` any single one.
+///
+/// ```
+/// use bt_parser::shared::glob::Glob;
+///
+/// let glob = Glob::new("*.sl2");
+/// assert!(glob.matches("ER0000.SL2"));
+/// assert!(!glob.matches("ER0000.sl2.bak"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Glob {
+    pattern: String,
+}
+
+impl Glob {
+    pub fn new(pattern: &str) -> Self {
+        Glob {
+            pattern: pattern.trim().into(),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.pattern
+    }
+
+    /// How many characters of a name the pattern pins down, so that
+    /// `ER????.sl2` ranks above `*.sl2`.
+    pub fn specificity(&self) -> usize {
+        self.pattern
+            .chars()
+            .filter(|&c| c != '*' && c != '?')
+            .count()
+    }
+
+    pub fn matches(&self, name: &str) -> bool {
+        let pattern: Vec<char> = self.pattern.to_lowercase().chars().collect();
+        let name: Vec<char> = name.to_lowercase().chars().collect();
+        let (mut p, mut n) = (0, 0);
+        // Where to resume after the most recent `*`: the pattern index past
+        // it and the name index it is currently matched up to.
+        let mut backtrack = None;
+        while n < name.len() {
+            match pattern.get(p) {
+                Some('*') => {
+                    backtrack = Some((p + 1, n));
+                    p += 1;
+                }
+                Some(&c) if c == '?' || c == name[n] => {
+                    p += 1;
+                    n += 1;
+                }
+                _ => match backtrack {
+                    Some((star_p, star_n)) => {
+                        backtrack = Some((star_p, star_n + 1));
+                        p = star_p;
+                        n = star_n + 1;
+                    }
+                    None => return false,
+                },
+            }
+        }
+        pattern[p..].iter().all(|&c| c == '*')
+    }
+}
+
+#[cfg(test)]
+mod glob_tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_matches() {
+        assert!(Glob::new("*").matches("anything.bin"));
+        assert!(Glob::new("*.zip").matches("archive.ZIP"));
+        assert!(Glob::new("ER????.sl2").matches("ER0000.sl2"));
+        assert!(Glob::new("*save*.dat").matches("my_save_01.dat"));
+        assert!(!Glob::new("ER????.sl2").matches("ER000.sl2"));
+        assert!(!Glob::new("*.zip").matches("archive.zip.txt"));
+        assert!(!Glob::new("*.zip").matches("zip"));
+    }
+
+    #[test]
+    fn test_glob_specificity() {
+        assert_eq!(Glob::new("*").specificity(), 0);
+        assert_eq!(Glob::new("*.sl2").specificity(), 4);
+        assert_eq!(Glob::new("ER????.sl2").specificity(), 6);
+    }
+}
@@ -1,28 +1,17 @@
-use nom::{
-    bytes::complete::{tag, take_until1},
-    combinator::map_res,
-    error::ParseError,
-    IResult,
-};
+use nom::{bytes::complete::take_until1, error::ParseError, IResult};
 
-fn get_remaining_input<'a>(
-    input: &'a str,
-    first_bracket_index: usize,
-    last_bracket_index: usize,
-) -> &'a str {
+fn get_remaining_input(input: &str, first_bracket_index: usize, last_bracket_index: usize) -> &str {
     let part2 = &input[last_bracket_index + 1..];
 
-    &input[..first_bracket_index + part2.len()].trim()
+    input[..first_bracket_index + part2.len()].trim()
 }
 
 /// A parser designed to work inside the `nom::sequence::delimited` parser, e.g.:
 /// ```
-/// use nom::bytes::complete::tag;
-/// use nom::sequence::delimited;
-/// use parse_hyperlinks::take_until_unbalanced;
+/// use bt_parser::shared::take_until_unbalanced::take_until_unbalanced;
 ///
-/// let mut parser = delimited(tag("<"), take_until_unbalanced('<', '>'), tag(">"));
-/// assert_eq!(parser("<<inside>inside>abc"), Ok(("abc", "<inside>inside")));
+/// let mut parser = take_until_unbalanced('(', ')');
+/// assert_eq!(parser("(a(b)c)"), Ok(("", "a(b)c")));
 /// ```
 /// It skips nested brackets until it finds an extra unbalanced closing bracket. Escaped brackets
 /// like `\<` and `\>` are not considered as brackets and are not counted. This function is
@@ -120,7 +109,7 @@ pub fn take_until_unbalanced<'a>(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use nom::error::ErrorKind;
+
     use pretty_assertions::assert_eq;
 
     #[test]
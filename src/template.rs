@@ -0,0 +1,260 @@
+use crate::{
+    ast::Template, error::ParseError, metadata::parse_metadata, parsing::statement::statement_list,
+};
+
+/// Parses a complete `.bt` template into its top-level items.
+///
+/// Statements are read one after another until the input is exhausted; the
+/// first statement that cannot be recognized aborts parsing with a
+/// [`ParseError`] pointing at its start. Statements that are recognized but
+/// malformed, such as an assignment to a literal, report the offending
+/// position instead. Comments documenting a typedef or declaration are
+/// attached to it, as described in [`statement_list`], and a leading
+/// header banner is also decoded into [`Template::metadata`]. A banner that
+/// cannot be decoded does not fail the template; it is reported in
+/// [`Template::warnings`] instead.
+///
+/// # Example
+///
+/// ```
+/// use bt_parser::ast::Statement;
+/// use bt_parser::parse_template;
+///
+/// let template = parse_template("// SL2 save\n\nint a; // unknown\n").unwrap();
+/// assert_eq!(template.items.len(), 2);
+/// assert_eq!(template.items[0], Statement::Comment("SL2 save".into()));
+/// ```
+pub fn parse_template(input: &str) -> Result<Template, ParseError> {
+    match statement_list(input) {
+        Ok(("", items)) => {
+            let (metadata, warnings) = match parse_metadata(input) {
+                Ok(metadata) => (metadata, Vec::new()),
+                Err(error) => (None, vec![error]),
+            };
+            Ok(Template {
+                items,
+                metadata,
+                warnings,
+            })
+        }
+        Err(nom::Err::Failure(error)) => Err(ParseError::from_failure(input, &error)),
+        Ok((rest, _)) => Err(ParseError::at(
+            input,
+            rest,
+            "expected a comment, typedef, declaration or conditional",
+        )),
+        Err(_) => Err(ParseError::at(input, input, "invalid syntax")),
+    }
+}
+
+#[cfg(test)]
+mod parse_template_tests {
+    use super::*;
+    use crate::ast::{Declaration, Expr, Expression, Literal, Statement};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_parse_template1() {
+        let input = r#"//------------------------------------------------
+//--- 010 Editor v14.0 Binary Template
+//------------------------------------------------
+
+typedef struct {
+  wchar_t  CharacterName[0x10];
+} PlayerGameData <size=0x1B0>;
+
+int32 unk;
+if (ItemID != 0) {
+    int32 unk2;
+}
+byte unk4;
+"#;
+        let template = parse_template(input).unwrap();
+        assert_eq!(template.items.len(), 7);
+        assert_eq!(
+            template.items[1],
+            Statement::Comment("--- 010 Editor v14.0 Binary Template".into())
+        );
+        assert!(matches!(template.items[3], Statement::Typedef(_)));
+        assert_eq!(
+            template.items[4],
+            Statement::Declaration(Declaration {
+                ty: "int32".into(),
+                name: Some("unk".into()),
+                args: vec![],
+                dimensions: vec![],
+                bitfield: None,
+                attributes: vec![],
+                doc: None,
+            })
+        );
+        match &template.items[5] {
+            Statement::If(statement) => {
+                assert_eq!(
+                    statement.condition,
+                    Expr::BinaryOp {
+                        left: Box::new(Expr::Identifier("ItemID".into())),
+                        op: Expression::NotEquals,
+                        right: Box::new(Expr::Literal(Literal::Int(0))),
+                    }
+                );
+                assert_eq!(
+                    statement.body,
+                    vec![Statement::Declaration(Declaration {
+                        ty: "int32".into(),
+                        name: Some("unk2".into()),
+                        args: vec![],
+                        dimensions: vec![],
+                        bitfield: None,
+                        attributes: vec![],
+                        doc: None,
+                    })]
+                );
+            }
+            _ => panic!("Expected Statement::If"),
+        }
+        assert_eq!(
+            template.items[6],
+            Statement::Declaration(Declaration {
+                ty: "byte".into(),
+                name: Some("unk4".into()),
+                args: vec![],
+                dimensions: vec![],
+                bitfield: None,
+                attributes: vec![],
+                doc: None,
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_template2() {
+        let template = parse_template("  \n\t").unwrap();
+        assert_eq!(
+            template,
+            Template {
+                items: vec![],
+                metadata: None,
+                warnings: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_template4() {
+        let input = "int a;\nif (a + 1 = 2) {\n}";
+        let error = parse_template(input).unwrap_err();
+        assert_eq!(
+            error,
+            ParseError {
+                line: 2,
+                column: 5,
+                message: "assignment target must be an identifier, member access or index".into(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_template3() {
+        let input = "int a;\nint b;\n  #include \"other.bt\"";
+        let error = parse_template(input).unwrap_err();
+        assert_eq!((error.line, error.column), (3, 3));
+    }
+
+    #[test]
+    fn test_parse_template_comments() {
+        let input = r#"/* File: PlayerGameData.bt
+   Purpose: SL2 save data */
+
+int32 /* signed */ unk; // unknown
+if (ItemID /* raw */ != 0) // has item
+{
+    int32 unk2;
+}
+"#;
+        let template = parse_template(input).unwrap();
+        assert_eq!(
+            template.items[0],
+            Statement::Comment("File: PlayerGameData.bt\n   Purpose: SL2 save data".into())
+        );
+        assert_eq!(
+            template.items[1],
+            Statement::Declaration(Declaration {
+                ty: "int32".into(),
+                name: Some("unk".into()),
+                args: vec![],
+                dimensions: vec![],
+                bitfield: None,
+                attributes: vec![],
+                doc: Some("unknown".into()),
+            })
+        );
+        assert!(matches!(template.items[2], Statement::If(_)));
+        assert_eq!(template.items.len(), 3);
+    }
+
+    #[test]
+    fn test_parse_template_doc_comments() {
+        let input = r#"// Size of the save slot
+// in bytes
+typedef struct {
+} Slot; /* fixed */
+
+// standalone
+
+byte unk4; // padding
+if (a) {
+} // after conditional
+"#;
+        let template = parse_template(input).unwrap();
+        match &template.items[0] {
+            Statement::Typedef(typedef) => assert_eq!(
+                typedef.doc.as_deref(),
+                Some("Size of the save slot\nin bytes\nfixed")
+            ),
+            _ => panic!("Expected Statement::Typedef"),
+        }
+        assert_eq!(template.items[1], Statement::Comment("standalone".into()));
+        match &template.items[2] {
+            Statement::Declaration(declaration) => {
+                assert_eq!(declaration.doc.as_deref(), Some("padding"))
+            }
+            _ => panic!("Expected Statement::Declaration"),
+        }
+        assert!(matches!(template.items[3], Statement::If(_)));
+        assert_eq!(
+            template.items[4],
+            Statement::Comment("after conditional".into())
+        );
+        assert_eq!(template.items.len(), 5);
+    }
+
+    #[test]
+    fn test_parse_template_unterminated_comment() {
+        let error = parse_template("int a;\n/* never closed\n").unwrap_err();
+        assert_eq!(
+            error,
+            ParseError {
+                line: 2,
+                column: 1,
+                message: "unterminated block comment".into(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_template_metadata() {
+        let input = "//------\n//      File: SL2.bt\n//  Category: Game\n//------\n\nint a;\n";
+        let template = parse_template(input).unwrap();
+        let metadata = template.metadata.unwrap();
+        assert_eq!(metadata.file.as_deref(), Some("SL2.bt"));
+        assert_eq!(metadata.category.as_deref(), Some("Game"));
+        assert_eq!(template.items.len(), 5);
+
+        let template = parse_template("// ID Bytes: XY\nint a;\n").unwrap();
+        assert_eq!(template.metadata, None);
+        assert_eq!(template.items.len(), 1);
+        assert_eq!(template.warnings.len(), 1);
+        assert_eq!(template.warnings[0].message, "invalid ID Bytes signature");
+    }
+}
@@ -1 +0,0 @@
-pub mod nested;
\ No newline at end of file